@@ -0,0 +1,16 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use quokka::lexer::lexer::Lexer;
+use quokka::parser::parser::Parser;
+
+//the lexer and parser must return, without panicking, on any input. Printing the program
+//also walks every node of the tree that was built.
+fuzz_target!(|data: &[u8]| {
+    if let Ok(input) = std::str::from_utf8(data) {
+        let mut parser = Parser::new(Lexer::new(input));
+        if let Some(program) = parser.parse_program() {
+            let _ = program.to_string();
+        }
+    }
+});
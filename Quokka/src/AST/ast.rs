@@ -1,25 +1,37 @@
 use std::fmt::Display;
 
-use crate::token::token::TokenType;
+use crate::token::token::{Span, TokenType};
+use num_bigint::BigInt;
 
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq)]
 pub enum Expression {
     Identifier(Identifier),
     Literal(Literal),
     Int(IntLiteral),
+    BigInt(BigIntLiteral),
+    Float(FloatLiteral),
+    Str(StringLiteral),
     Prefix(Box<PrefixExpression>),
     Infix(Box<InfixExpression>),
     BoolenExpr(Boolen),
     If(Box<IfStatment>),
     Func(FunctionLiteral),
     Call(Box<CallExpression>),
+    While(Box<WhileExpression>),
+    For(Box<ForExpression>),
+    Assign(Box<AssignExpression>),
+    Array(ArrayLiteral),
+    Index(Box<IndexExpression>),
+    Hash(HashLiteral),
     Blank,
 }
 
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq)]
 pub enum Statment {
     Let(LetStatment),
     Return(ReturnStatment),
+    Break(BreakStatment),
+    Continue(ContinueStatment),
     Expr(Expression),
 }
 
@@ -28,83 +40,267 @@ pub struct Program {
     pub statments: Vec<Statment>,
 }
 
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq)]
 pub struct Literal {
     pub value: String,
+    pub span: Span,
 }
 
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq)]
 pub struct IntLiteral {
-    pub value: i32,
+    pub value: i64,
+    pub span: Span,
 }
 
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq)]
+pub struct BigIntLiteral {
+    pub value: BigInt,
+    pub span: Span,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct FloatLiteral {
+    pub value: f64,
+    pub span: Span,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct StringLiteral {
+    pub value: String,
+    pub span: Span,
+}
+
+#[derive(Clone, PartialEq)]
 pub struct Boolen {
     pub value: bool,
     pub tok_type: TokenType,
+    pub span: Span,
 }
 
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq)]
 pub struct LetStatment {
     pub ident: Identifier,
     pub value: Expression,
+    pub span: Span,
 }
 
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq)]
 pub struct Identifier {
     pub value: String,
+    pub span: Span,
 }
 
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq)]
 pub struct ReturnStatment {
     pub return_value: Expression,
+    pub span: Span,
 }
 
-#[derive(Clone, PartialEq, Eq)]
+//Clone, PartialEq and Drop walk an else if chain in a loop, the chain can be longer than the
+//native stack would allow recursing through.
 pub struct IfStatment {
     pub condition: Expression,
     pub consequence: BlockStatment,
-    pub alternative: Option<BlockStatment>,
+    pub alternative: Option<ElseBranch>,
+    pub span: Span,
+}
+
+//`else { }` ends a chain, `else if` continues it with another if statment.
+#[derive(Clone, PartialEq)]
+pub enum ElseBranch {
+    Block(BlockStatment),
+    If(Box<IfStatment>),
+}
+
+#[derive(Clone, PartialEq)]
+pub struct WhileExpression {
+    pub condition: Expression,
+    pub body: BlockStatment,
+    pub span: Span,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct ForExpression {
+    pub ident: Identifier,
+    pub iterable: Expression,
+    pub body: BlockStatment,
+    pub span: Span,
+}
+
+//`x = value`, or a compound assignment such as `x += value` when operator is set.
+#[derive(Clone, PartialEq)]
+pub struct AssignExpression {
+    pub ident: Identifier,
+    pub operator: Option<Infix>,
+    pub value: Expression,
+    pub span: Span,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct BreakStatment {
+    pub span: Span,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct ContinueStatment {
+    pub span: Span,
 }
 
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq)]
 pub struct BlockStatment {
     pub stmts: Vec<Statment>,
+    pub span: Span,
 }
 
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq)]
 pub struct FunctionLiteral {
+    //set from the let statment binding the function, empty for anonymous functions.
+    pub name: String,
     pub params: Vec<Identifier>,
     pub body: BlockStatment,
+    pub span: Span,
 }
 
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq)]
 pub struct CallExpression {
     pub arguments: Vec<Expression>,
     pub function: Expression,
+    pub span: Span,
 }
 
-#[derive(Clone)]
-pub struct ExpressionStatment {
-    pub expr: Expression,
+#[derive(Clone, PartialEq)]
+pub struct ArrayLiteral {
+    pub elements: Vec<Expression>,
+    pub span: Span,
+}
+
+//pairs are kept in source order, which is also the order the hash prints in.
+#[derive(Clone, PartialEq)]
+pub struct HashLiteral {
+    pub pairs: Vec<(Expression, Expression)>,
+    pub span: Span,
 }
 
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq)]
+pub struct IndexExpression {
+    pub lhs: Expression,
+    pub index: Expression,
+    pub span: Span,
+}
+
+#[derive(Clone, PartialEq)]
 pub struct PrefixExpression {
-    pub tok_type: TokenType,
-    pub operator: String,
+    pub operator: Prefix,
     pub rhs: Expression,
+    pub span: Span,
 }
 
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq)]
 pub struct InfixExpression {
-    pub tok_type: TokenType,
     pub lhs: Expression,
-    pub operator: String,
+    pub operator: Infix,
     pub rhs: Expression,
+    pub span: Span,
+}
+
+impl Expression {
+    pub fn span(&self) -> Span {
+        match self {
+            Expression::Identifier(ident) => ident.span,
+            Expression::Literal(lit) => lit.span,
+            Expression::Int(num) => num.span,
+            Expression::BigInt(num) => num.span,
+            Expression::Float(num) => num.span,
+            Expression::Str(s) => s.span,
+            Expression::Prefix(p_ex) => p_ex.span,
+            Expression::Infix(i_ex) => i_ex.span,
+            Expression::BoolenExpr(bool) => bool.span,
+            Expression::If(stmt) => stmt.span,
+            Expression::Func(func) => func.span,
+            Expression::Call(c) => c.span,
+            Expression::While(w) => w.span,
+            Expression::For(f) => f.span,
+            Expression::Assign(a) => a.span,
+            Expression::Array(arr) => arr.span,
+            Expression::Index(idx) => idx.span,
+            Expression::Hash(hash) => hash.span,
+            Expression::Blank => Span::default(),
+        }
+    }
+}
+
+impl Statment {
+    pub fn span(&self) -> Span {
+        match self {
+            Statment::Let(l) => l.span,
+            Statment::Return(ret) => ret.span,
+            Statment::Break(b) => b.span,
+            Statment::Continue(c) => c.span,
+            Statment::Expr(expr) => expr.span(),
+        }
+    }
+}
+
+impl IfStatment {
+    //links up the branches of an else if chain, each one's alternative is the next.
+    pub fn chain(mut branches: Vec<IfStatment>, last: Option<ElseBranch>) -> Option<IfStatment> {
+        let mut expr = branches.pop()?;
+        expr.alternative = last;
+        while let Some(mut prev) = branches.pop() {
+            prev.alternative = Some(ElseBranch::If(Box::new(expr)));
+            expr = prev;
+        }
+        return Some(expr);
+    }
+}
+
+impl Clone for IfStatment {
+    fn clone(&self) -> Self {
+        let mut branches = Vec::new();
+        let mut curr = self;
+        loop {
+            branches.push(IfStatment {
+                condition: curr.condition.clone(),
+                consequence: curr.consequence.clone(),
+                alternative: None,
+                span: curr.span,
+            });
+            match &curr.alternative {
+                Some(ElseBranch::If(next)) => curr = next,
+                last => return IfStatment::chain(branches, last.clone()).unwrap(),
+            }
+        }
+    }
 }
 
-#[derive(PartialEq, Clone, Debug)]
+impl PartialEq for IfStatment {
+    fn eq(&self, other: &Self) -> bool {
+        let (mut lhs, mut rhs) = (self, other);
+        loop {
+            if lhs.condition != rhs.condition
+                || lhs.consequence != rhs.consequence
+                || lhs.span != rhs.span
+            {
+                return false;
+            }
+            match (&lhs.alternative, &rhs.alternative) {
+                (Some(ElseBranch::If(l)), Some(ElseBranch::If(r))) => (lhs, rhs) = (l, r),
+                (l, r) => return l == r,
+            }
+        }
+    }
+}
+
+impl Drop for IfStatment {
+    fn drop(&mut self) {
+        let mut next = self.alternative.take();
+        while let Some(ElseBranch::If(mut stmt)) = next {
+            next = stmt.alternative.take();
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
 pub enum Infix {
     Plus,
     Minus,
@@ -116,13 +312,89 @@ pub enum Infix {
     GreaterThan,
     LessThanEqual,
     LessThan,
+    And,
+    Or,
+    Modulo,
+    Power,
+    BitAnd,
+    BitOr,
+    BitXor,
+    ShiftLeft,
+    ShiftRight,
 }
 
-#[derive(PartialEq, Clone, Debug)]
+#[derive(PartialEq, Clone, Copy, Debug)]
 pub enum Prefix {
     Plus,
     Minus,
     Not,
+    BitNot,
+}
+
+impl Infix {
+    pub fn from_token(tok: &TokenType) -> Option<Infix> {
+        match tok {
+            TokenType::Plus => Some(Infix::Plus),
+            TokenType::Minus => Some(Infix::Minus),
+            TokenType::Fslash => Some(Infix::Divide),
+            TokenType::Asterisk => Some(Infix::Multiply),
+            TokenType::EQ => Some(Infix::Equal),
+            TokenType::NotEQ => Some(Infix::NotEqual),
+            TokenType::RarrowEQ => Some(Infix::GreaterThanEqual),
+            TokenType::Rarrow => Some(Infix::GreaterThan),
+            TokenType::LarrowEQ => Some(Infix::LessThanEqual),
+            TokenType::Larrow => Some(Infix::LessThan),
+            TokenType::And => Some(Infix::And),
+            TokenType::Or => Some(Infix::Or),
+            TokenType::Percent => Some(Infix::Modulo),
+            TokenType::Power => Some(Infix::Power),
+            TokenType::Ampersand => Some(Infix::BitAnd),
+            TokenType::Pipe => Some(Infix::BitOr),
+            TokenType::Caret => Some(Infix::BitXor),
+            TokenType::LShift => Some(Infix::ShiftLeft),
+            TokenType::RShift => Some(Infix::ShiftRight),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Infix {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let op = match self {
+            Infix::Plus => "+",
+            Infix::Minus => "-",
+            Infix::Divide => "/",
+            Infix::Multiply => "*",
+            Infix::Equal => "==",
+            Infix::NotEqual => "!=",
+            Infix::GreaterThanEqual => ">=",
+            Infix::GreaterThan => ">",
+            Infix::LessThanEqual => "<=",
+            Infix::LessThan => "<",
+            Infix::And => "&&",
+            Infix::Or => "||",
+            Infix::Modulo => "%",
+            Infix::Power => "**",
+            Infix::BitAnd => "&",
+            Infix::BitOr => "|",
+            Infix::BitXor => "^",
+            Infix::ShiftLeft => "<<",
+            Infix::ShiftRight => ">>",
+        };
+        write!(f, "{}", op)
+    }
+}
+
+impl Display for Prefix {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let op = match self {
+            Prefix::Plus => "+",
+            Prefix::Minus => "-",
+            Prefix::Not => "!",
+            Prefix::BitNot => "~",
+        };
+        write!(f, "{}", op)
+    }
 }
 
 impl Display for FunctionLiteral {
@@ -132,7 +404,7 @@ impl Display for FunctionLiteral {
             params.push(p.to_string());
         }
         let s = params.join(", ");
-        write!(f, "fn ({}) {}", s, self.body.to_string())
+        write!(f, "fn ({}) {}", s, self.body)
     }
 }
 
@@ -142,24 +414,71 @@ impl Display for CallExpression {
         for a in &self.arguments {
             args.push(a.to_string());
         }
-        write!(f, "{}({} )", self.function.to_string(), args.join(", "))
+        write!(f, "{}({} )", self.function, args.join(", "))
     }
 }
 
 impl Display for IfStatment {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "if {} {}", self.condition, self.consequence);
+        let mut curr = self;
+        loop {
+            write!(f, "if {} {}", curr.condition, curr.consequence)?;
+            match &curr.alternative {
+                Some(ElseBranch::If(next)) => {
+                    write!(f, "else ")?;
+                    curr = next;
+                }
+                Some(ElseBranch::Block(alt)) => return write!(f, "else {}", alt),
+                None => return Ok(()),
+            }
+        }
+    }
+}
+impl Display for WhileExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "while {} {}", self.condition, self.body)
+    }
+}
 
-        if self.alternative.is_some() {
-            write!(f, "{}", self.alternative.clone().unwrap());
+impl Display for ForExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "for ({} in {}) {}", self.ident, self.iterable, self.body)
+    }
+}
+
+impl Display for AssignExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.operator {
+            Some(op) => write!(f, "{} {}= {}", self.ident, op, self.value),
+            None => write!(f, "{} = {}", self.ident, self.value),
         }
-        Ok(())
     }
 }
+
+impl Display for ArrayLiteral {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut elements = vec![];
+        for e in &self.elements {
+            elements.push(e.to_string());
+        }
+        write!(f, "[{}]", elements.join(", "))
+    }
+}
+
+impl Display for HashLiteral {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut pairs = vec![];
+        for (key, value) in &self.pairs {
+            pairs.push(format!("{}: {}", key, value));
+        }
+        write!(f, "{{{}}}", pairs.join(", "))
+    }
+}
+
 impl Display for BlockStatment {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         for stmt in self.stmts.iter() {
-            write!(f, "{}", stmt);
+            write!(f, "{}", stmt)?;
         }
         Ok(())
     }
@@ -173,7 +492,7 @@ impl Display for Identifier {
 
 impl Display for PrefixExpression {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.rhs.to_string())
+        write!(f, "{}", self.rhs)
     }
 }
 
@@ -183,20 +502,26 @@ impl Display for Expression {
             Expression::Literal(lit) => write!(f, "{}", lit.value),
             Expression::Identifier(ident) => write!(f, "{}", ident.value),
             Expression::Int(num) => write!(f, "{}", num.value),
+            Expression::BigInt(num) => write!(f, "{}", num.value),
+            Expression::Float(num) => write!(f, "{:?}", num.value),
+            Expression::Str(s) => write!(f, "{:?}", s.value),
             Expression::Prefix(p_ex) => {
-                write!(f, "({}", p_ex.operator);
-                write!(f, "{})", p_ex.rhs)
+                write!(f, "({}{})", p_ex.operator, p_ex.rhs)
             }
             Expression::Infix(i_ex) => {
-                write!(f, "({}", i_ex.lhs);
-                write!(f, " {} ", i_ex.operator);
-                write!(f, "{})", i_ex.rhs)
+                write!(f, "({} {} {})", i_ex.lhs, i_ex.operator, i_ex.rhs)
             }
             Expression::Blank => write!(f, ""),
             Expression::BoolenExpr(bool) => write!(f, "{}", bool.value),
             Expression::If(stmt) => write!(f, "{}", stmt),
             Expression::Func(func) => write!(f, "{}", func),
             Expression::Call(c) => write!(f, "{}", c),
+            Expression::While(w) => write!(f, "{}", w),
+            Expression::For(for_expr) => write!(f, "{}", for_expr),
+            Expression::Assign(a) => write!(f, "{}", a),
+            Expression::Array(arr) => write!(f, "{}", arr),
+            Expression::Hash(hash) => write!(f, "{}", hash),
+            Expression::Index(idx) => write!(f, "({}[{}])", idx.lhs, idx.index),
         }
     }
 }
@@ -209,27 +534,23 @@ impl Display for Literal {
 
 impl Display for LetStatment {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "let {} = {};",
-            self.ident.to_string(),
-            self.value.to_string()
-        )
+        write!(f, "let {} = {};", self.ident, self.value)
     }
 }
 
 impl Display for ReturnStatment {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "return {}", self.return_value.to_string());
-        write!(f, ";")
+        write!(f, "return {};", self.return_value)
     }
 }
 
 impl Display for Statment {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         return match self {
-            Statment::Let(l) => write!(f, "{}", l.to_string()),
-            Statment::Return(ret) => write!(f, "{}", ret.to_string()),
+            Statment::Let(l) => write!(f, "{}", l),
+            Statment::Return(ret) => write!(f, "{}", ret),
+            Statment::Break(_) => write!(f, "break;"),
+            Statment::Continue(_) => write!(f, "continue;"),
             Statment::Expr(expr) => write!(f, "{}", expr),
         };
     }
@@ -238,8 +559,8 @@ impl Display for Statment {
 impl Display for Program {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         for stmt in &self.statments {
-            write!(f, "{}", stmt.to_string());
+            write!(f, "{}", stmt)?;
         }
-        write!(f, "")
+        Ok(())
     }
 }
@@ -1,7 +1,6 @@
 #[cfg(test)]
 mod test {
-    use core::panic;
-
+    use crate::token::token::Span;
     use crate::AST::ast::{Expression, Identifier, LetStatment, Program, Statment};
 
     #[test]
@@ -12,18 +11,21 @@ mod test {
 
         let ident = Identifier {
             value: "myvar".to_string(),
+            span: Span::default(),
         };
 
         let expr = Expression::Identifier(Identifier {
             value: "anotherVar".to_string(),
+            span: Span::default(),
         });
 
-        let letStmt = &LetStatment {
-            ident: ident,
+        let let_stmt = &LetStatment {
+            ident,
             value: expr,
+            span: Span::default(),
         };
 
-        prog.statments.push(Statment::Let(letStmt.clone()));
+        prog.statments.push(Statment::Let(let_stmt.clone()));
 
         assert_eq!(prog.to_string(), "let myvar = anotherVar;")
     }
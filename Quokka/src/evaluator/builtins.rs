@@ -0,0 +1,207 @@
+use std::collections::HashMap;
+
+use lazy_static::lazy_static;
+use num_bigint::BigInt;
+use num_traits::{FromPrimitive, ToPrimitive};
+
+use super::error::ErrorKind;
+use super::object::{Obj, Object};
+
+pub type BuiltinFn = fn(Vec<Object>) -> Object;
+
+//a native function, call_func checks the argument count against min_args and max_args
+//before calling func, so func only has to check the argument types.
+#[derive(Clone, Copy)]
+pub struct Builtin {
+    pub name: &'static str,
+    pub min_args: usize,
+    //None when the function takes any number of arguments from min_args up.
+    pub max_args: Option<usize>,
+    pub func: BuiltinFn,
+}
+
+//builtins are unique by name, comparing the function pointers wouldn't be reliable.
+impl PartialEq for Builtin {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl Builtin {
+    const fn new(
+        name: &'static str,
+        min_args: usize,
+        max_args: Option<usize>,
+        func: BuiltinFn,
+    ) -> Builtin {
+        Builtin {
+            name,
+            min_args,
+            max_args,
+            func,
+        }
+    }
+
+    pub fn check_arity(&self, got: usize) -> Result<(), String> {
+        let fits = got >= self.min_args && self.max_args.is_none_or(|max| got <= max);
+        if fits {
+            return Ok(());
+        }
+        let want = match self.max_args {
+            Some(max) if max == self.min_args => max.to_string(),
+            Some(max) if max == self.min_args + 1 => format!("{} or {}", self.min_args, max),
+            Some(max) => format!("{} to {}", self.min_args, max),
+            None => format!("at least {}", self.min_args),
+        };
+        Err(format!(
+            "wrong number of arguments to {}. got={}, want={}",
+            self.name, got, want
+        ))
+    }
+}
+
+lazy_static! {
+    static ref builtins: HashMap<&'static str, Builtin> = HashMap::from([
+        ("len", Builtin::new("len", 1, Some(1), len)),
+        ("puts", Builtin::new("puts", 0, None, puts)),
+        ("first", Builtin::new("first", 1, Some(1), first)),
+        ("last", Builtin::new("last", 1, Some(1), last)),
+        ("rest", Builtin::new("rest", 1, Some(1), rest)),
+        ("push", Builtin::new("push", 2, Some(2), push)),
+        ("type", Builtin::new("type", 1, Some(1), type_of)),
+        ("range", Builtin::new("range", 2, Some(3), range)),
+        ("int", Builtin::new("int", 1, Some(1), int)),
+        ("float", Builtin::new("float", 1, Some(1), float)),
+    ]);
+}
+
+pub fn lookup_builtin(name: &str) -> Option<Builtin> {
+    builtins.get(name).copied()
+}
+
+fn unsupported_arg(name: &str, arg: &Object) -> Object {
+    Object::error(
+        ErrorKind::InvalidArgument,
+        format!("argument to {} not supported, got {}", name, arg.Type()),
+    )
+}
+
+fn len(args: Vec<Object>) -> Object {
+    match &args[0] {
+        Object::String(s) => Object::Integer(s.chars().count() as i64),
+        Object::Array(elements) => Object::Integer(elements.len() as i64),
+        Object::Hash(hash) => Object::Integer(hash.pairs().len() as i64),
+        arg => unsupported_arg("len", arg),
+    }
+}
+
+fn puts(args: Vec<Object>) -> Object {
+    for arg in &args {
+        println!("{}", arg);
+    }
+    Object::Null
+}
+
+fn first(args: Vec<Object>) -> Object {
+    match &args[0] {
+        Object::Array(elements) => elements.first().cloned().unwrap_or(Object::Null),
+        arg => unsupported_arg("first", arg),
+    }
+}
+
+fn last(args: Vec<Object>) -> Object {
+    match &args[0] {
+        Object::Array(elements) => elements.last().cloned().unwrap_or(Object::Null),
+        arg => unsupported_arg("last", arg),
+    }
+}
+
+//a new array without the first element, null for an empty array.
+fn rest(args: Vec<Object>) -> Object {
+    match &args[0] {
+        Object::Array(elements) if elements.is_empty() => Object::Null,
+        Object::Array(elements) => Object::Array(elements[1..].to_vec()),
+        arg => unsupported_arg("rest", arg),
+    }
+}
+
+//arrays are values, push returns a new array and leaves its argument unchanged.
+fn push(args: Vec<Object>) -> Object {
+    match &args[0] {
+        Object::Array(elements) => {
+            let mut elements = elements.clone();
+            elements.push(args[1].clone());
+            Object::Array(elements)
+        }
+        arg => unsupported_arg("push", arg),
+    }
+}
+
+fn type_of(args: Vec<Object>) -> Object {
+    Object::String(args[0].Type())
+}
+
+//floats are truncated towards zero, one too large for an i64 becomes a big integer.
+fn int(args: Vec<Object>) -> Object {
+    match &args[0] {
+        Object::Integer(_) | Object::BigInteger(_) => args[0].clone(),
+        Object::Float(num) => match BigInt::from_f64(num.trunc()) {
+            Some(num) => Object::from_big_int(num),
+            None => Object::error(
+                ErrorKind::OutOfRange,
+                format!("cannot convert {} to INTEGER", num),
+            ),
+        },
+        arg => unsupported_arg("int", arg),
+    }
+}
+
+//integers beyond the float range are an error rather than infinity.
+fn float(args: Vec<Object>) -> Object {
+    let num = match &args[0] {
+        Object::Integer(i) => *i as f64,
+        Object::BigInteger(num) => num.to_f64().unwrap_or(f64::INFINITY),
+        Object::Float(num) => *num,
+        arg => return unsupported_arg("float", arg),
+    };
+    if num.is_infinite() && !matches!(args[0], Object::Float(_)) {
+        return Object::error(
+            ErrorKind::OutOfRange,
+            "integer is too large for FLOAT".to_string(),
+        );
+    }
+    Object::Float(num)
+}
+
+fn range(args: Vec<Object>) -> Object {
+    let mut bounds = Vec::new();
+    for arg in &args {
+        match arg {
+            Object::Integer(i) => bounds.push(*i),
+            Object::BigInteger(num) => {
+                return Object::error(
+                    ErrorKind::OutOfRange,
+                    format!("range argument is too large: {}", num),
+                )
+            }
+            _ => {
+                return Object::error(
+                    ErrorKind::InvalidArgument,
+                    format!("range arguments must be INTEGER, got {}", arg.Type()),
+                )
+            }
+        }
+    }
+    let step = bounds.get(2).copied().unwrap_or(1);
+    if step == 0 {
+        return Object::error(
+            ErrorKind::InvalidArgument,
+            "range step cannot be zero".to_string(),
+        );
+    }
+    Object::Range {
+        start: bounds[0],
+        end: bounds[1],
+        step,
+    }
+}
@@ -0,0 +1,139 @@
+use crate::token::token::Span;
+use std::fmt::Display;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ErrorKind {
+    TypeMismatch,
+    UnknownOperator,
+    UndefinedIdentifier,
+    DivisionByZero,
+    NotCallable,
+    ArityMismatch,
+    //a builtin was given an argument of a type or value it can't use.
+    InvalidArgument,
+    //bad index or hash key.
+    InvalidIndex,
+    NotIterable,
+    //a number too large for the operation, like a huge exponent or shift.
+    OutOfRange,
+    StackOverflow,
+    //break or continue escaping the loop, the parser normally rejects these.
+    InvalidControlFlow,
+    UnknownExpression,
+}
+
+//a call to a Quokka function that the error unwound through.
+#[derive(Clone, PartialEq)]
+pub struct Frame {
+    //empty for anonymous functions.
+    pub name: String,
+    //where the function was called from.
+    pub span: Span,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct RuntimeError {
+    pub kind: ErrorKind,
+    pub message: String,
+    //the innermost expression that failed, set by the evaluator as the error propagates.
+    pub span: Option<Span>,
+    //innermost call first.
+    pub frames: Vec<Frame>,
+}
+
+impl RuntimeError {
+    pub fn new(kind: ErrorKind, message: String) -> RuntimeError {
+        RuntimeError {
+            kind,
+            message,
+            span: None,
+            frames: Vec::new(),
+        }
+    }
+
+    //the error and the calls leading to it, outermost call first.
+    pub fn traceback(&self) -> String {
+        return self.format_traceback(None);
+    }
+
+    //like traceback, but locations are given as file:line:col, for errors in scripts.
+    pub fn traceback_in(&self, file: &str) -> String {
+        return self.format_traceback(Some(file));
+    }
+
+    fn format_traceback(&self, file: Option<&str>) -> String {
+        let location = |span: Span| match file {
+            Some(file) => format!("{}:{}", file, span),
+            None => span.to_string(),
+        };
+        let mut out = String::new();
+        if !self.frames.is_empty() {
+            out.push_str("Traceback (most recent call last):\n");
+            let calls: Vec<String> = self
+                .frames
+                .iter()
+                .rev()
+                .map(|frame| {
+                    let name = match frame.name.as_str() {
+                        "" => "<anonymous>",
+                        name => name,
+                    };
+                    format!("  {} called at {}", name, location(frame.span))
+                })
+                .collect();
+            for line in shorten_traceback(collapse_repeats(calls)) {
+                out.push_str(&line);
+                out.push('\n');
+            }
+        }
+        out.push_str(&format!("Error: {}", self));
+        if let Some(span) = self.span {
+            out.push_str(&format!(" at {}", location(span)));
+        }
+        out
+    }
+}
+
+//a run of identical lines is shown this many times before the rest is counted.
+const MAX_REPEATED_LINES: usize = 3;
+//lines kept at each end of a traceback too long to print whole.
+const TRACEBACK_EDGE_LINES: usize = 20;
+
+//recursion fails with the same call repeated thousands of times.
+fn collapse_repeats(lines: Vec<String>) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let run = lines[i..].iter().take_while(|l| **l == lines[i]).count();
+        for _ in 0..run.min(MAX_REPEATED_LINES) {
+            out.push(lines[i].clone());
+        }
+        if run > MAX_REPEATED_LINES {
+            out.push(format!(
+                "  [previous line repeated {} more times]",
+                run - MAX_REPEATED_LINES
+            ));
+        }
+        i += run;
+    }
+    return out;
+}
+
+//mutual recursion alternates between calls and doesn't collapse, keep only both ends.
+fn shorten_traceback(mut lines: Vec<String>) -> Vec<String> {
+    if lines.len() <= 2 * TRACEBACK_EDGE_LINES {
+        return lines;
+    }
+    let hidden = lines.len() - 2 * TRACEBACK_EDGE_LINES;
+    let tail = lines.split_off(lines.len() - TRACEBACK_EDGE_LINES);
+    lines.truncate(TRACEBACK_EDGE_LINES);
+    lines.push(format!("  [{} more lines not shown]", hidden));
+    lines.extend(tail);
+    return lines;
+}
+
+impl Display for RuntimeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
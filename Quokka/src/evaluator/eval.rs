@@ -1,55 +1,302 @@
 use crate::evaluator::object::Object;
-use crate::AST::ast::{BlockStatment, Expression, Identifier, IfStatment, LetStatment, Statment};
+use crate::token::token::Span;
+use crate::AST::ast::{
+    AssignExpression, CallExpression, ElseBranch, Expression, ForExpression, HashLiteral,
+    Identifier, IfStatment, Infix, InfixExpression, LetStatment, Prefix, Statment, WhileExpression,
+};
+use num_bigint::BigInt;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+use std::cell::Cell;
 
-use super::object::{Enviornment, Obj};
-use crate::new_error;
+use super::builtins::lookup_builtin;
+use super::error::{ErrorKind, Frame, RuntimeError};
+use super::object::{Enviornment, HashObject, Obj};
+
+//calls nested deeper than this return a stack overflow error instead of exhausting the
+//native stack, see set_max_call_depth.
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 10_000;
+//the native stack used by one call of a small function in an unoptimised build, bigger
+//bodies take more and are stopped by the stack limit instead, see set_stack_size.
+pub const STACK_BYTES_PER_CALL: usize = 16 * 1024;
+//the stack eval assumes it has when set_stack_size wasn't called on the thread, the default
+//for threads spawned by std and the smallest one commonly seen.
+pub const DEFAULT_STACK_SIZE: usize = 2 * 1024 * 1024;
+
+//the largest integer ** and << may produce, in bits. Without a bound a single operator
+//could tie up the interpreter for minutes or exhaust memory.
+pub const MAX_INT_BITS: u64 = 1 << 20;
+
+//stack left unused by the evaluator, for the native code run between two stack checks and
+//for unwinding with the error.
+const STACK_RED_ZONE: usize = 1024 * 1024;
+
+thread_local! {
+    static CALL_DEPTH: Cell<usize> = const { Cell::new(0) };
+    static MAX_CALL_DEPTH: Cell<usize> = const { Cell::new(DEFAULT_MAX_CALL_DEPTH) };
+    //lowest stack address evaluation may reach, 0 until set_stack_size or the first eval.
+    static STACK_LIMIT: Cell<usize> = const { Cell::new(0) };
+}
+
+//every call that isn't a tail call uses native stack, so the thread running eval needs
+//roughly max_depth * STACK_BYTES_PER_CALL bytes of stack.
+pub fn set_max_call_depth(max_depth: usize) {
+    MAX_CALL_DEPTH.with(|max| max.set(max_depth));
+}
+
+//the call depth limit alone can't protect the native stack, a single call with a deeply
+//nested body uses far more than STACK_BYTES_PER_CALL. Given the size of the current
+//thread's stack, evaluation fails with a stack overflow error before it runs out. Call it
+//near the start of the thread, otherwise the first eval assumes DEFAULT_STACK_SIZE.
+pub fn set_stack_size(bytes: usize) {
+    let limit = stack_address().saturating_sub(bytes.saturating_sub(STACK_RED_ZONE));
+    STACK_LIMIT.with(|l| l.set(limit));
+}
+
+//the stack grows down on every platform we run on.
+fn stack_address() -> usize {
+    let marker = 0u8;
+    std::hint::black_box(&marker) as *const u8 as usize
+}
+
+fn check_stack() -> Result<(), RuntimeError> {
+    if stack_address() < STACK_LIMIT.with(|l| l.get()) {
+        return Err(RuntimeError::new(
+            ErrorKind::StackOverflow,
+            "stack overflow: evaluation nested too deeply".to_string(),
+        ));
+    }
+    Ok(())
+}
 
 pub fn eval(stmt: &Statment, env: &mut Enviornment) -> Option<Object> {
+    if STACK_LIMIT.with(|l| l.get()) == 0 {
+        set_stack_size(DEFAULT_STACK_SIZE);
+    }
+    let result = eval_stmt(stmt, env)?;
+    //a top level `return f(x)` leaves its call for us to run.
+    if let Object::ReturnValue(val) = result {
+        return match run_tail_calls(*val) {
+            //a failed return is reported as the error itself, like any other failed statment.
+            err @ Object::Error(_) => Some(err),
+            val => Some(Object::ReturnValue(Box::new(val))),
+        };
+    }
+    Some(result)
+}
+
+fn eval_stmt(stmt: &Statment, env: &mut Enviornment) -> Option<Object> {
     match stmt {
         Statment::Expr(e) => Some(eval_expr(e, env)),
         Statment::Let(l) => eval_let_stmt(l, env),
-        Statment::Return(r) => Some(Object::ReturnValue(Box::new(eval_expr(
-            &r.return_value,
-            env,
-        )))),
+        Statment::Break(_) => Some(Object::Break),
+        Statment::Continue(_) => Some(Object::Continue),
+        Statment::Return(r) => {
+            let value = eval_tail_expr(&r.return_value, env);
+            if is_abrupt(&value) {
+                return Some(value);
+            }
+            Some(Object::ReturnValue(Box::new(value)))
+        }
     }
 }
+
+//errors from expr's own evaluation point at expr, errors from a subexpression already point
+//at the subexpression that failed.
 fn eval_expr(expr: &Expression, env: &mut Enviornment) -> Object {
+    if let Err(err) = check_stack() {
+        return located(Object::Error(err), expr.span());
+    }
+    located(eval_expr_kind(expr, env), expr.span())
+}
+
+fn eval_expr_kind(expr: &Expression, env: &mut Enviornment) -> Object {
     match expr {
         Expression::Int(i) => return Object::Integer(i.value),
+        Expression::BigInt(num) => return Object::from_big_int(num.value.clone()),
+        Expression::Float(num) => return Object::Float(num.value),
+        Expression::Str(s) => return Object::String(s.value.clone()),
         Expression::BoolenExpr(b) => return Object::Boolean(b.value),
         Expression::Prefix(pre) => {
             let right = eval_expr(&pre.rhs, env);
+            if is_abrupt(&right) {
+                return right;
+            }
             return eval_prefix_expr(&pre.operator, &right);
         }
+        Expression::Infix(infix) if matches!(infix.operator, Infix::And | Infix::Or) => {
+            return eval_logical_expr(infix, env);
+        }
         Expression::Infix(infix) => {
             let lhs = eval_expr(&infix.lhs, env);
+            if is_abrupt(&lhs) {
+                return lhs;
+            }
             let rhs = eval_expr(&infix.rhs, env);
+            if is_abrupt(&rhs) {
+                return rhs;
+            }
 
             return eval_infix_expr(&lhs, &rhs, &infix.operator);
         }
-        Expression::If(if_stmt) => return eval_if_expr(if_stmt, env),
+        Expression::If(if_stmt) => return eval_if_expr(if_stmt, env, false),
+        Expression::While(w) => return eval_while_expr(w, env),
+        Expression::For(f) => return eval_for_expr(f, env),
+        Expression::Assign(a) => return eval_assign_expr(a, env),
+        Expression::Array(arr) => {
+            return match eval_expressions(&arr.elements, env) {
+                Ok(elements) => Object::Array(elements),
+                Err(obj) => *obj,
+            };
+        }
+        Expression::Hash(hash) => return eval_hash_literal(hash, env),
+        Expression::Index(idx) => {
+            let lhs = eval_expr(&idx.lhs, env);
+            if is_abrupt(&lhs) {
+                return lhs;
+            }
+            let index = eval_expr(&idx.index, env);
+            if is_abrupt(&index) {
+                return index;
+            }
+            return eval_index_expr(&lhs, &index);
+        }
         Expression::Identifier(ident) => return eval_ident(ident.clone(), env),
         Expression::Func(f) => return Object::Function(f.clone(), env.clone()),
         Expression::Call(c) => {
-            let function = eval_expr(&c.function, env);
-            let args = eval_expressions(&c.arguments, env);
-            if c.arguments.len() == 1 && matches!(&args[0], Object::Error(_)) {
-                return args[0].clone();
-            }
-            return apply_func(&function, args);
+            return match eval_call_parts(c, env) {
+                Ok((function, args)) => apply_func(&function, args, c.span),
+                Err(obj) => *obj,
+            };
         }
-        _ => return Object::Error("unknown expression, @eval_expr".to_string()),
+        _ => {
+            return Object::error(
+                ErrorKind::UnknownExpression,
+                format!("unknown expression: {}", expr),
+            )
+        }
+    }
+}
+
+//like eval_expr, but a call in tail position is returned as Object::TailCall instead of being
+//applied, so the caller can run it after its own frame is gone.
+fn eval_tail_expr(expr: &Expression, env: &mut Enviornment) -> Object {
+    match expr {
+        Expression::Call(c) => match eval_call_parts(c, env) {
+            Ok((function, args)) => Object::TailCall {
+                func: Box::new(function),
+                args,
+                span: c.span,
+            },
+            Err(obj) => *obj,
+        },
+        Expression::If(if_stmt) => eval_if_expr(if_stmt, env, true),
+        _ => eval_expr(expr, env),
     }
 }
 
-fn apply_func(func: &Object, args: Vec<Object>) -> Object {
+//Err holds whatever stopped the evaluation, see is_abrupt.
+fn eval_call_parts(
+    c: &CallExpression,
+    env: &mut Enviornment,
+) -> Result<(Object, Vec<Object>), Box<Object>> {
+    let function = eval_expr(&c.function, env);
+    if is_abrupt(&function) {
+        return Err(Box::new(function));
+    }
+    let args = eval_expressions(&c.arguments, env)?;
+    Ok((function, args))
+}
+
+fn apply_func(func: &Object, args: Vec<Object>, span: Span) -> Object {
+    run_tail_calls(call_func(func, args, span))
+}
+
+//the trampoline, each tail call runs after the call that made it has returned, so tail
+//recursion runs in constant native stack.
+fn run_tail_calls(obj: Object) -> Object {
+    let mut result = obj;
+    while let Object::TailCall { func, args, span } = result {
+        result = call_func(&func, args, span);
+    }
+    result
+}
+
+//span is the call expression, errors the call itself raises point at it.
+fn call_func(func: &Object, args: Vec<Object>, span: Span) -> Object {
+    if let Object::Builtin(builtin) = func {
+        if let Err(message) = builtin.check_arity(args.len()) {
+            return located(Object::error(ErrorKind::ArityMismatch, message), span);
+        }
+        return located((builtin.func)(args), span);
+    }
     if let Object::Function(f, _) = func {
+        if f.params.len() != args.len() {
+            return located(
+                Object::error(
+                    ErrorKind::ArityMismatch,
+                    format!(
+                        "wrong number of arguments. got={}, want={}",
+                        args.len(),
+                        f.params.len()
+                    ),
+                ),
+                span,
+            );
+        }
+        let depth = CALL_DEPTH.with(|depth| depth.get());
+        let max_depth = MAX_CALL_DEPTH.with(|max| max.get());
+        if depth >= max_depth {
+            return located(
+                Object::error(
+                    ErrorKind::StackOverflow,
+                    format!(
+                        "stack overflow: maximum call depth of {} exceeded",
+                        max_depth
+                    ),
+                ),
+                span,
+            );
+        }
+        CALL_DEPTH.with(|d| d.set(depth + 1));
         let mut extended_env = extened_func_env(func, args);
-        let evaluated = eval_statments(&f.body.stmts, &mut extended_env);
-        return unwrap_return_value(evaluated);
+        let evaluated = eval_statments(&f.body.stmts, &mut extended_env, true);
+        CALL_DEPTH.with(|d| d.set(depth));
+        return match unwrap_return_value(evaluated) {
+            //the parser rejects these, a loop signal must never escape its function.
+            signal @ (Object::Break | Object::Continue) => located(
+                Object::error(
+                    ErrorKind::InvalidControlFlow,
+                    format!("{} outside of a loop", signal),
+                ),
+                span,
+            ),
+            Object::Error(mut err) => {
+                err.frames.push(Frame {
+                    name: f.name.clone(),
+                    span,
+                });
+                Object::Error(err)
+            }
+            result => result,
+        };
+    }
+    located(
+        Object::error(
+            ErrorKind::NotCallable,
+            format!("not a function: {}", func.Type()),
+        ),
+        span,
+    )
+}
+
+fn located(obj: Object, span: Span) -> Object {
+    match obj {
+        Object::Error(mut err) if err.span.is_none() => {
+            err.span = Some(span);
+            Object::Error(err)
+        }
+        _ => obj,
     }
-    return Object::Error(format!("object is not a function. Got:{}", func.Type()));
 }
 
 fn unwrap_return_value(obj: Object) -> Object {
@@ -61,7 +308,7 @@ fn unwrap_return_value(obj: Object) -> Object {
 
 fn extened_func_env(func: &Object, args: Vec<Object>) -> Enviornment {
     if let Object::Function(f, env) = func {
-        let mut e = Enviornment::new_enclosed_env(&mut env.clone());
+        let e = Enviornment::new_enclosed_env(env);
 
         for (i, param) in f.params.iter().enumerate() {
             e.set(param.to_string(), &args[i]);
@@ -71,44 +318,116 @@ fn extened_func_env(func: &Object, args: Vec<Object>) -> Enviornment {
     return Enviornment::new();
 }
 
-fn eval_expressions(exp: &Vec<Expression>, env: &mut Enviornment) -> Vec<Object> {
+fn eval_expressions(
+    exp: &Vec<Expression>,
+    env: &mut Enviornment,
+) -> Result<Vec<Object>, Box<Object>> {
     let mut res = Vec::new();
     for e in exp {
         let eval = eval_expr(e, env);
-        if let Object::Error(e) = eval {
-            return vec![Object::Error(e)];
+        if is_abrupt(&eval) {
+            return Err(Box::new(eval));
         }
         res.push(eval);
     }
-    return res;
+    return Ok(res);
 }
 
+//errors, returns and loop signals stop everything up to the function or loop that handles
+//them, so they must never be used as a value, bound or stored.
+fn is_abrupt(obj: &Object) -> bool {
+    matches!(
+        obj,
+        Object::Error(_) | Object::ReturnValue(_) | Object::Break | Object::Continue
+    )
+}
+
+//user bindings shadow builtins, so a builtin is only used when the name isn't bound.
 fn eval_ident(ident: Identifier, env: &mut Enviornment) -> Object {
-    env.get(&ident.to_string())
-}
-
-fn eval_if_expr(stmt: &IfStatment, env: &mut Enviornment) -> Object {
-    let cond = eval_expr(&stmt.condition, env);
-    if is_truthy(&cond) {
-        return eval_statments(&stmt.consequence.stmts, env);
-    }
-    if stmt.alternative.is_some() {
-        return eval_statments(
-            &stmt
-                .alternative
-                .clone()
-                .unwrap_or(BlockStatment { stmts: Vec::new() })
-                .stmts,
-            env,
-        );
+    let obj = env.get(&ident.to_string());
+    if let Object::Error(_) = obj {
+        if let Some(builtin) = lookup_builtin(&ident.value) {
+            return Object::Builtin(builtin);
+        }
+    }
+    obj
+}
+
+fn eval_if_expr(stmt: &IfStatment, env: &mut Enviornment, tail: bool) -> Object {
+    let mut curr = stmt;
+    loop {
+        let cond = eval_expr(&curr.condition, env);
+        if is_abrupt(&cond) {
+            return cond;
+        }
+        if is_truthy(&cond) {
+            return eval_statments(&curr.consequence.stmts, env, tail);
+        }
+        match &curr.alternative {
+            Some(ElseBranch::If(next)) => curr = next,
+            Some(ElseBranch::Block(alt)) => return eval_statments(&alt.stmts, env, tail),
+            None => return Object::Null,
+        }
+    }
+}
+
+//the body runs in env itself, like an if block, only for loops open a scope per iteration.
+fn eval_while_expr(w: &WhileExpression, env: &mut Enviornment) -> Object {
+    loop {
+        let cond = eval_expr(&w.condition, env);
+        if is_abrupt(&cond) {
+            return cond;
+        }
+        if !is_truthy(&cond) {
+            return Object::Null;
+        }
+        match eval_statments(&w.body.stmts, env, false) {
+            Object::Break => return Object::Null,
+            res @ (Object::ReturnValue(_) | Object::Error(_)) => return res,
+            _ => {}
+        }
+    }
+}
+
+//every iteration gets its own enclosed scope, so closures capture that iteration's value.
+fn eval_for_expr(f: &ForExpression, env: &mut Enviornment) -> Object {
+    let iterable = eval_expr(&f.iterable, env);
+    if is_abrupt(&iterable) {
+        return iterable;
+    }
+    let items = match iterable.iter() {
+        Some(items) => items,
+        None => {
+            return Object::error(
+                ErrorKind::NotIterable,
+                format!("{} is not iterable", iterable.Type()),
+            )
+        }
+    };
+    for item in items {
+        let mut iter_env = Enviornment::new_enclosed_env(env);
+        iter_env.set(f.ident.value.clone(), &item);
+        match eval_statments(&f.body.stmts, &mut iter_env, false) {
+            Object::Break => return Object::Null,
+            res @ (Object::ReturnValue(_) | Object::Error(_)) => return res,
+            _ => {}
+        }
     }
     Object::Null
 }
 
-fn eval_statments(stmts: &Vec<Statment>, env: &mut Enviornment) -> Object {
+//with tail set a call that is the value of the block is left as an Object::TailCall.
+fn eval_statments(stmts: &[Statment], env: &mut Enviornment, tail: bool) -> Object {
+    //blocks in tail position nest without going through eval_expr.
+    if let Err(err) = check_stack() {
+        return Object::Error(err);
+    }
     let mut result = Some(Object::Null);
-    for stmt in stmts {
-        result = eval(stmt, env);
+    for (i, stmt) in stmts.iter().enumerate() {
+        result = match stmt {
+            Statment::Expr(e) if tail && i == stmts.len() - 1 => Some(eval_tail_expr(e, env)),
+            _ => eval_stmt(stmt, env),
+        };
 
         if result.is_some() {
             if let Object::ReturnValue(v) = result.clone().unwrap_or(Object::Null) {
@@ -117,6 +436,9 @@ fn eval_statments(stmts: &Vec<Statment>, env: &mut Enviornment) -> Object {
             if let Object::Error(err) = result.clone().unwrap_or(Object::Null) {
                 return Object::Error(err);
             }
+            if let Some(signal @ (Object::Break | Object::Continue)) = result {
+                return signal;
+            }
         }
     }
     result.unwrap_or(Object::Null)
@@ -128,71 +450,241 @@ fn is_truthy(obj: &Object) -> bool {
             return true;
         }
     }
+    if let Object::BigInteger(num) = obj {
+        return num.is_positive();
+    }
+    if let Object::Float(num) = obj {
+        return *num > 0.0;
+    }
     if let Object::Boolean(b) = obj {
         return *b;
     }
     return false;
 }
 
-fn eval_infix_expr(lhs: &Object, rhs: &Object, op: &str) -> Object {
+//the rhs is only evaluated when the lhs does not already decide the result.
+fn eval_logical_expr(infix: &InfixExpression, env: &mut Enviornment) -> Object {
+    let lhs = eval_expr(&infix.lhs, env);
+    if is_abrupt(&lhs) {
+        return lhs;
+    }
+    let lhs_truthy = is_truthy(&lhs);
+    if (infix.operator == Infix::And && !lhs_truthy) || (infix.operator == Infix::Or && lhs_truthy)
+    {
+        return Object::Boolean(lhs_truthy);
+    }
+    let rhs = eval_expr(&infix.rhs, env);
+    if is_abrupt(&rhs) {
+        return rhs;
+    }
+    Object::Boolean(is_truthy(&rhs))
+}
+
+fn eval_infix_expr(lhs: &Object, rhs: &Object, op: &Infix) -> Object {
     if let Object::Integer(first) = rhs {
         if let Object::Integer(sec) = lhs {
             return eval_int_infix_expr(*sec, *first, op);
         }
     }
+    if let (Some(l), Some(r)) = (lhs.to_big_int(), rhs.to_big_int()) {
+        return eval_big_int_infix_expr(&l, &r, op);
+    }
+    if let (Object::String(l), Object::String(r)) = (lhs, rhs) {
+        return eval_string_infix_expr(l, r, op);
+    }
+    //bitwise operators are only defined for integers, which were handled above.
+    if matches!(
+        op,
+        Infix::BitAnd | Infix::BitOr | Infix::BitXor | Infix::ShiftLeft | Infix::ShiftRight
+    ) {
+        return unknown_infix_operator(&lhs.Type(), op, &rhs.Type());
+    }
+    //at least one side is a float, the other is converted.
+    if let (Some(l), Some(r)) = (lhs.to_float(), rhs.to_float()) {
+        return eval_float_infix_expr(l, r, op);
+    }
     if lhs.Type() != rhs.Type() {
-        return create_new_error(new_error!(
-            "type mismatch:".to_string(),
-            lhs.Type(),
-            op.to_string(),
-            rhs.Type()
-        ));
+        return Object::error(
+            ErrorKind::TypeMismatch,
+            format!("type mismatch: {} {} {}", lhs.Type(), op, rhs.Type()),
+        );
     }
     match op {
-        "==" => return Object::Boolean(lhs == rhs),
-        "!=" => return Object::Boolean(lhs != rhs),
+        Infix::Equal => return Object::Boolean(lhs == rhs),
+        Infix::NotEqual => return Object::Boolean(lhs != rhs),
         _ => {
-            return create_new_error(new_error!(
-                "unknown operator:".to_string(),
-                lhs.Type(),
-                op.to_string(),
-                rhs.Type()
-            ));
+            return unknown_infix_operator(&lhs.Type(), op, &rhs.Type());
+        }
+    };
+}
+
+//arithmetic is checked, results that overflow an i64 are redone as big integers.
+fn eval_int_infix_expr(lhs: i64, rhs: i64, op: &Infix) -> Object {
+    let result = match op {
+        Infix::Plus => lhs.checked_add(rhs),
+        Infix::Minus => lhs.checked_sub(rhs),
+        Infix::Divide => {
+            if rhs == 0 {
+                return Object::error(ErrorKind::DivisionByZero, "division by zero".to_string());
+            }
+            lhs.checked_div(rhs)
+        }
+        Infix::Multiply => lhs.checked_mul(rhs),
+        Infix::Modulo => {
+            if rhs == 0 {
+                return Object::error(ErrorKind::DivisionByZero, "division by zero".to_string());
+            }
+            lhs.checked_rem(rhs)
         }
+        Infix::Power => {
+            if rhs < 0 {
+                return Object::Float((lhs as f64).powf(rhs as f64));
+            }
+            match u32::try_from(rhs) {
+                Ok(exp) => lhs.checked_pow(exp),
+                Err(_) => None,
+            }
+        }
+        Infix::BitAnd => Some(lhs & rhs),
+        Infix::BitOr => Some(lhs | rhs),
+        Infix::BitXor => Some(lhs ^ rhs),
+        //shifts need range checks on the amount, the big integer path handles them.
+        Infix::ShiftLeft | Infix::ShiftRight => None,
+        Infix::LessThan => return Object::Boolean(lhs < rhs),
+        Infix::GreaterThan => return Object::Boolean(lhs > rhs),
+        Infix::LessThanEqual => return Object::Boolean(lhs <= rhs),
+        Infix::GreaterThanEqual => return Object::Boolean(lhs >= rhs),
+        Infix::Equal => return Object::Boolean(lhs == rhs),
+        Infix::NotEqual => return Object::Boolean(lhs != rhs),
+        _ => return unknown_infix_operator("INTEGER", op, "INTEGER"),
     };
+    match result {
+        Some(num) => Object::Integer(num),
+        None => eval_big_int_infix_expr(&BigInt::from(lhs), &BigInt::from(rhs), op),
+    }
 }
 
-fn eval_int_infix_expr(lhs: i32, rhs: i32, op: &str) -> Object {
+fn eval_big_int_infix_expr(lhs: &BigInt, rhs: &BigInt, op: &Infix) -> Object {
     match op {
-        "+" => Object::Integer(lhs + rhs),
-        "-" => Object::Integer(lhs - rhs),
-        "/" => {
-            if rhs == 0 && lhs == 0 || rhs == 0 && lhs != 0 {
-                return Object::Error("Division by zero is not allowed.".to_string());
-            }
-            return Object::Integer(lhs / rhs);
-        }
-        "*" => Object::Integer(lhs * rhs),
-        "<" => Object::Boolean(lhs < rhs),
-        ">" => Object::Boolean(lhs > rhs),
-        "==" => Object::Boolean(lhs == rhs),
-        "!=" => Object::Boolean(lhs != rhs),
-        _ => {
-            return create_new_error(new_error!(
-                "unknown operator: ",
-                lhs.to_string(),
-                op.to_string(),
-                rhs.to_string()
-            ))
+        Infix::Plus => Object::from_big_int(lhs + rhs),
+        Infix::Minus => Object::from_big_int(lhs - rhs),
+        Infix::Divide => {
+            if rhs.is_zero() {
+                return Object::error(ErrorKind::DivisionByZero, "division by zero".to_string());
+            }
+            return Object::from_big_int(lhs / rhs);
+        }
+        Infix::Multiply => Object::from_big_int(lhs * rhs),
+        Infix::Modulo => {
+            if rhs.is_zero() {
+                return Object::error(ErrorKind::DivisionByZero, "division by zero".to_string());
+            }
+            return Object::from_big_int(lhs % rhs);
+        }
+        Infix::Power => {
+            if rhs.is_negative() {
+                let (l, r) = (
+                    lhs.to_f64().unwrap_or(f64::NAN),
+                    rhs.to_f64().unwrap_or(f64::NAN),
+                );
+                return Object::Float(l.powf(r));
+            }
+            //these never grow, so any exponent is fine.
+            if lhs.is_zero() || lhs.magnitude().is_one() {
+                let odd = rhs.bit(0);
+                return match lhs.to_i64() {
+                    Some(0) if rhs.is_zero() => Object::Integer(1),
+                    Some(-1) if !odd => Object::Integer(1),
+                    _ => Object::from_big_int(lhs.clone()),
+                };
+            }
+            //the result has at most lhs.bits() * exp bits.
+            match rhs.to_u32() {
+                Some(exp) if lhs.bits().saturating_mul(u64::from(exp)) <= MAX_INT_BITS => {
+                    Object::from_big_int(lhs.pow(exp))
+                }
+                _ => Object::error(ErrorKind::OutOfRange, "exponent is too large".to_string()),
+            }
+        }
+        Infix::BitAnd => Object::from_big_int(lhs & rhs),
+        Infix::BitOr => Object::from_big_int(lhs | rhs),
+        Infix::BitXor => Object::from_big_int(lhs ^ rhs),
+        Infix::ShiftLeft | Infix::ShiftRight => {
+            if rhs.is_negative() {
+                return Object::error(ErrorKind::OutOfRange, "negative shift amount".to_string());
+            }
+            match (op, rhs.to_u32()) {
+                (Infix::ShiftLeft, _) if lhs.is_zero() => Object::Integer(0),
+                (Infix::ShiftLeft, Some(amount))
+                    if lhs.bits() + u64::from(amount) <= MAX_INT_BITS =>
+                {
+                    Object::from_big_int(lhs << amount)
+                }
+                (Infix::ShiftLeft, _) => Object::error(
+                    ErrorKind::OutOfRange,
+                    "shift amount is too large".to_string(),
+                ),
+                //shifting right past every bit leaves only the sign.
+                (_, amount) => Object::from_big_int(lhs >> amount.unwrap_or(u32::MAX)),
+            }
+        }
+        Infix::LessThan => Object::Boolean(lhs < rhs),
+        Infix::GreaterThan => Object::Boolean(lhs > rhs),
+        Infix::LessThanEqual => Object::Boolean(lhs <= rhs),
+        Infix::GreaterThanEqual => Object::Boolean(lhs >= rhs),
+        Infix::Equal => Object::Boolean(lhs == rhs),
+        Infix::NotEqual => Object::Boolean(lhs != rhs),
+        _ => unknown_infix_operator("INTEGER", op, "INTEGER"),
+    }
+}
+
+fn eval_float_infix_expr(lhs: f64, rhs: f64, op: &Infix) -> Object {
+    match op {
+        Infix::Plus => Object::Float(lhs + rhs),
+        Infix::Minus => Object::Float(lhs - rhs),
+        Infix::Divide => {
+            if rhs == 0.0 {
+                return Object::error(ErrorKind::DivisionByZero, "division by zero".to_string());
+            }
+            return Object::Float(lhs / rhs);
         }
+        Infix::Multiply => Object::Float(lhs * rhs),
+        Infix::Modulo => {
+            if rhs == 0.0 {
+                return Object::error(ErrorKind::DivisionByZero, "division by zero".to_string());
+            }
+            return Object::Float(lhs % rhs);
+        }
+        Infix::Power => Object::Float(lhs.powf(rhs)),
+        Infix::LessThan => Object::Boolean(lhs < rhs),
+        Infix::GreaterThan => Object::Boolean(lhs > rhs),
+        Infix::LessThanEqual => Object::Boolean(lhs <= rhs),
+        Infix::GreaterThanEqual => Object::Boolean(lhs >= rhs),
+        Infix::Equal => Object::Boolean(lhs == rhs),
+        Infix::NotEqual => Object::Boolean(lhs != rhs),
+        _ => unknown_infix_operator("FLOAT", op, "FLOAT"),
+    }
+}
+
+fn eval_string_infix_expr(lhs: &str, rhs: &str, op: &Infix) -> Object {
+    match op {
+        Infix::Plus => Object::String(format!("{}{}", lhs, rhs)),
+        Infix::LessThan => Object::Boolean(lhs < rhs),
+        Infix::GreaterThan => Object::Boolean(lhs > rhs),
+        Infix::LessThanEqual => Object::Boolean(lhs <= rhs),
+        Infix::GreaterThanEqual => Object::Boolean(lhs >= rhs),
+        Infix::Equal => Object::Boolean(lhs == rhs),
+        Infix::NotEqual => Object::Boolean(lhs != rhs),
+        _ => unknown_infix_operator("STRING", op, "STRING"),
     }
 }
 
-fn eval_prefix_expr(op: &str, rhs: &Object) -> Object {
+fn eval_prefix_expr(op: &Prefix, rhs: &Object) -> Object {
     match op {
-        "!" => return eval_bang_expr(&rhs),
-        "-" => return eval_minus_prefix(&rhs),
-        _ => create_new_error(new_error!("unknown operator: ", op.to_string(), rhs.Type())),
+        Prefix::Not => return eval_bang_expr(rhs),
+        Prefix::Minus => return eval_minus_prefix(rhs),
+        Prefix::Plus => return eval_plus_prefix(rhs),
+        Prefix::BitNot => return eval_bit_not_prefix(rhs),
     }
 }
 
@@ -200,48 +692,146 @@ fn eval_bang_expr(rhs: &Object) -> Object {
     match rhs {
         Object::Boolean(b) => return Object::Boolean(!b),
         Object::Integer(val) => {
-            return Object::Boolean(if val.is_positive() { false } else { true });
+            return Object::Boolean(!val.is_positive());
         }
+        Object::Float(_) | Object::BigInteger(_) => return Object::Boolean(!is_truthy(rhs)),
 
-        _ => create_new_error(new_error!(
-            "unknown operator: ",
-            "!".to_string(),
-            rhs.Type()
-        )),
+        _ => unknown_prefix_operator(&Prefix::Not, rhs),
     }
 }
 
 fn eval_minus_prefix(rhs: &Object) -> Object {
     if let Object::Integer(i) = rhs {
-        return Object::Integer(-i);
+        return match i.checked_neg() {
+            Some(num) => Object::Integer(num),
+            None => Object::BigInteger(-BigInt::from(*i)),
+        };
+    }
+    if let Object::BigInteger(num) = rhs {
+        return Object::from_big_int(-num);
+    }
+    if let Object::Float(num) = rhs {
+        return Object::Float(-num);
+    }
+    unknown_prefix_operator(&Prefix::Minus, rhs)
+}
+
+fn eval_plus_prefix(rhs: &Object) -> Object {
+    match rhs {
+        Object::Integer(_) | Object::BigInteger(_) | Object::Float(_) => rhs.clone(),
+        _ => unknown_prefix_operator(&Prefix::Plus, rhs),
+    }
+}
+
+fn eval_bit_not_prefix(rhs: &Object) -> Object {
+    match rhs {
+        Object::Integer(i) => Object::Integer(!i),
+        Object::BigInteger(num) => Object::from_big_int(!num),
+        _ => unknown_prefix_operator(&Prefix::BitNot, rhs),
+    }
+}
+
+fn eval_hash_literal(hash: &HashLiteral, env: &mut Enviornment) -> Object {
+    let mut obj = HashObject::new();
+    for (key_expr, value_expr) in &hash.pairs {
+        let key = eval_expr(key_expr, env);
+        if is_abrupt(&key) {
+            return key;
+        }
+        let hash_key = match key.hash_key() {
+            Some(hash_key) => hash_key,
+            None => {
+                return Object::error(
+                    ErrorKind::InvalidIndex,
+                    format!("unusable as hash key: {}", key.Type()),
+                )
+            }
+        };
+        let value = eval_expr(value_expr, env);
+        if is_abrupt(&value) {
+            return value;
+        }
+        obj.insert(hash_key, key, value);
     }
-    create_new_error(new_error!("unknown operator: -".to_string(), rhs.Type()))
+    Object::Hash(obj)
+}
+
+fn eval_index_expr(lhs: &Object, index: &Object) -> Object {
+    match (lhs, index) {
+        (Object::Array(elements), Object::Integer(i)) => {
+            //negative indices count from the end, -1 is the last element.
+            let pos = if *i < 0 {
+                i.checked_add(elements.len() as i64)
+            } else {
+                Some(*i)
+            };
+            match pos.and_then(|p| usize::try_from(p).ok()) {
+                Some(p) if p < elements.len() => elements[p].clone(),
+                _ => Object::Null,
+            }
+        }
+        (Object::Array(_), Object::BigInteger(_)) => Object::Null,
+        (Object::Hash(hash), _) => match index.hash_key() {
+            Some(key) => hash.get(&key).cloned().unwrap_or(Object::Null),
+            None => Object::error(
+                ErrorKind::InvalidIndex,
+                format!("unusable as hash key: {}", index.Type()),
+            ),
+        },
+        (Object::Array(_), _) => Object::error(
+            ErrorKind::InvalidIndex,
+            format!("index must be an INTEGER, got {}", index.Type()),
+        ),
+        _ => Object::error(
+            ErrorKind::InvalidIndex,
+            format!("index operator not supported: {}", lhs.Type()),
+        ),
+    }
+}
+
+fn eval_assign_expr(a: &AssignExpression, env: &mut Enviornment) -> Object {
+    let mut value = eval_expr(&a.value, env);
+    if is_abrupt(&value) {
+        return value;
+    }
+    if let Some(op) = &a.operator {
+        let current = env.get(&a.ident.value);
+        if let Object::Error(_) = current {
+            return current;
+        }
+        value = eval_infix_expr(&current, &value, op);
+        if let Object::Error(_) = value {
+            return value;
+        }
+    }
+    if !env.assign(&a.ident.value, &value) {
+        return Object::error(
+            ErrorKind::UndefinedIdentifier,
+            format!("cannot assign to undeclared identifier: {}", a.ident.value),
+        );
+    }
+    value
 }
 
 fn eval_let_stmt(s: &LetStatment, env: &mut Enviornment) -> Option<Object> {
     let value = eval_expr(&s.value, env);
-    if let Object::Error(_) = &value {
+    if is_abrupt(&value) {
         return Some(value);
     }
     env.set(s.ident.to_string(), &value);
     Some(value)
 }
 
-#[macro_use]
-mod error_macros {
-    #[macro_export]
-    macro_rules! new_error {
-        ($s:expr) => {
-            $s
-        };
-        ($s:expr,$($additonal_expr:expr),+) => {
-            {
-                let out : String = format!("{} {}",$s,new_error!($($additonal_expr),+));
-                out
-            }
-        }
-    }
+fn unknown_infix_operator(lhs: &str, op: &Infix, rhs: &str) -> Object {
+    Object::error(
+        ErrorKind::UnknownOperator,
+        format!("unknown operator: {} {} {}", lhs, op, rhs),
+    )
 }
-fn create_new_error(message: String) -> Object {
-    Object::Error(message)
+
+fn unknown_prefix_operator(op: &Prefix, rhs: &Object) -> Object {
+    Object::error(
+        ErrorKind::UnknownOperator,
+        format!("unknown operator: {}{}", op, rhs.Type()),
+    )
 }
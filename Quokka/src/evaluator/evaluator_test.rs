@@ -1,7 +1,10 @@
 #[cfg(test)]
 mod tests {
-    use self::object::{Enviornment, Object};
-    use crate::evaluator::eval::eval;
+    use self::error::ErrorKind;
+    use self::object::{Enviornment, HashKey, Object};
+    use crate::evaluator::eval::{
+        eval, set_max_call_depth, set_stack_size, DEFAULT_MAX_CALL_DEPTH,
+    };
     use crate::evaluator::*;
     use crate::lexer::lexer::Lexer;
     use crate::parser::parser::Parser;
@@ -10,10 +13,10 @@ mod tests {
     fn test_eval_int_expr() {
         struct Test<'a> {
             input: &'a str,
-            expected: i32,
+            expected: i64,
         }
         impl<'a> Test<'a> {
-            fn new(inp: &'a str, exp: i32) -> Test {
+            fn new(inp: &'a str, exp: i64) -> Test<'a> {
                 Test {
                     input: inp,
                     expected: exp,
@@ -49,10 +52,10 @@ mod tests {
     fn test_func_application() {
         struct Test<'a> {
             input: &'a str,
-            expected: i32,
+            expected: i64,
         }
         impl<'a> Test<'a> {
-            fn new(inp: &'a str, exp: i32) -> Test<'a> {
+            fn new(inp: &'a str, exp: i64) -> Test<'a> {
                 Test {
                     input: inp,
                     expected: exp,
@@ -95,7 +98,7 @@ mod tests {
         let tests = vec![
             Test::new("5 + true;", "type mismatch: INTEGER + BOOLEAN"),
             Test::new("5 + true;5;", "type mismatch: INTEGER + BOOLEAN"),
-            Test::new("-true", "unknown operator: - BOOLEAN"),
+            Test::new("-true", "unknown operator: -BOOLEAN"),
             Test::new("true + false;", "unknown operator: BOOLEAN + BOOLEAN"),
             Test::new("5;true + false;5", "unknown operator: BOOLEAN + BOOLEAN"),
             Test::new(
@@ -109,7 +112,7 @@ mod tests {
             let evaluated = test_eval_helper(t_case.input.to_string());
 
             if let Object::Error(err) = evaluated.unwrap() {
-                if t_case.expected != err {
+                if t_case.expected != err.message {
                     panic!(
                         "Unexpected error message, Expected: {}, Got: {}",
                         t_case.expected, err
@@ -143,7 +146,7 @@ mod tests {
             }
             let expected = "(x + 2)";
             if f.body.to_string() != expected {
-                panic!("body is not {}, Got: {}", expected, f.body.to_string());
+                panic!("body is not {}, Got: {}", expected, f.body);
             }
             return;
         }
@@ -154,10 +157,10 @@ mod tests {
     fn test_let_statments() {
         struct Test<'a> {
             input: &'a str,
-            expected: i32,
+            expected: i64,
         }
         impl<'a> Test<'a> {
-            fn new(inp: &'a str, exp: i32) -> Test {
+            fn new(inp: &'a str, exp: i64) -> Test<'a> {
                 Test {
                     input: inp,
                     expected: exp,
@@ -183,10 +186,10 @@ mod tests {
     fn test_return_statments() {
         struct Test<'a> {
             input: &'a str,
-            expected: i32,
+            expected: i64,
         }
         impl<'a> Test<'a> {
-            fn new(inp: &'a str, exp: i32) -> Test {
+            fn new(inp: &'a str, exp: i64) -> Test<'a> {
                 Test {
                     input: inp,
                     expected: exp,
@@ -225,7 +228,7 @@ mod tests {
             expected: Object,
         }
         impl<'a> Test<'a> {
-            fn new(inp: &'a str, exp: Object) -> Test {
+            fn new(inp: &'a str, exp: Object) -> Test<'a> {
                 Test {
                     input: inp,
                     expected: exp,
@@ -240,6 +243,19 @@ mod tests {
             Test::new("if (1 > 2){ 10 }", Object::Null),
             Test::new("if (1 > 2){ 10 } else { 20 }", Object::Integer(20)),
             Test::new("if (1 < 2){ 10 } else { 20 }", Object::Integer(10)),
+            Test::new(
+                "if (1 > 2){ 10 } else if (2 > 1){ 20 } else { 30 }",
+                Object::Integer(20),
+            ),
+            Test::new(
+                "if (1 > 2){ 10 } else if (2 > 3){ 20 } else { 30 }",
+                Object::Integer(30),
+            ),
+            Test::new("if (false){ 10 } else if (false){ 20 }", Object::Null),
+            Test::new(
+                "let x = 3; if (x == 1){ 1 } else if (x == 2){ 2 } else if (x == 3){ 3 } else { 4 }",
+                Object::Integer(3),
+            ),
         ];
 
         for t_case in &tests {
@@ -263,7 +279,7 @@ mod tests {
             expected: bool,
         }
         impl<'a> Test<'a> {
-            fn new(inp: &'a str, exp: bool) -> Test {
+            fn new(inp: &'a str, exp: bool) -> Test<'a> {
                 Test {
                     input: inp,
                     expected: exp,
@@ -291,6 +307,14 @@ mod tests {
             Test::new("1 != 1", false),
             Test::new("1 == 2", false),
             Test::new("1 != 2", true),
+            Test::new("1 <= 1", true),
+            Test::new("2 <= 1", false),
+            Test::new("1 >= 1", true),
+            Test::new("1 >= 2", false),
+            Test::new("1.5 <= 2", true),
+            Test::new("2 >= 1.5", true),
+            Test::new("9223372036854775808 >= 9223372036854775807", true),
+            Test::new("(1 <= 2) == (2 >= 1)", true),
         ];
 
         for t_case in tests.iter() {
@@ -306,7 +330,7 @@ mod tests {
             expected: bool,
         }
         impl<'a> Test<'a> {
-            fn new(inp: &'a str, exp: bool) -> Test {
+            fn new(inp: &'a str, exp: bool) -> Test<'a> {
                 Test {
                     input: inp,
                     expected: exp,
@@ -349,11 +373,7 @@ mod tests {
     }
 
     fn test_eval_helper(input: String) -> Option<Object> {
-        let mut l = Lexer {
-            ch: 'l',
-            input: input.clone(),
-        };
-        let lex = Lexer::new(&mut l, input);
+        let lex = Lexer::new(&input);
         let mut prsr = Parser::new(lex);
 
         let program = prsr.parse_program();
@@ -362,11 +382,9 @@ mod tests {
         }
         let mut env = Enviornment::new();
         let mut evaluated = eval(&program.clone().unwrap().statments[0], &mut env);
-        if &program.clone().unwrap().statments.len() > &1 {
-            let mut i = 0;
-            for _ in &program.clone().unwrap().statments {
-                evaluated = eval(&program.clone().unwrap().statments[i], &mut env);
-                i += 1;
+        if program.clone().unwrap().statments.len() > 1 {
+            for stmt in &program.clone().unwrap().statments {
+                evaluated = eval(stmt, &mut env);
                 if let Object::ReturnValue(_) = evaluated.clone().unwrap() {
                     return evaluated;
                 }
@@ -378,7 +396,7 @@ mod tests {
         return evaluated;
     }
 
-    fn test_int_obj_helper(obj: Object, expected: i32) {
+    fn test_int_obj_helper(obj: Object, expected: i64) {
         if let Object::Integer(i) = obj {
             if i != expected {
                 panic!(
@@ -388,6 +406,1184 @@ mod tests {
             }
             return;
         }
-        panic!("Object is not an integer.");
+        panic!("Object is not an integer. Got: {}", obj);
+    }
+
+    #[test]
+    fn test_string_expr() {
+        let tests = vec![
+            (r#""Hello World!""#, "Hello World!"),
+            (r#"'Hello' + ' ' + 'World'"#, "Hello World"),
+            (
+                r#"let greet = fn(name){ "Hi, " + name; }; greet('Quokka')"#,
+                "Hi, Quokka",
+            ),
+            (r#""tab\there""#, "tab\there"),
+        ];
+
+        for (input, expected) in &tests {
+            match test_eval_helper(input.to_string()).unwrap() {
+                Object::String(s) => assert_eq!(&s, expected),
+                obj => panic!("Object is not a String. Got: {}", obj),
+            }
+        }
+    }
+
+    #[test]
+    fn test_string_comparison() {
+        let tests = vec![
+            ("'a' == 'a'", true),
+            ("'a' == 'b'", false),
+            ("'a' != 'b'", true),
+            ("'a' < 'b'", true),
+            ("'b' < 'a'", false),
+            ("'abc' > 'abd'", false),
+            ("'b' > 'abc'", true),
+            ("'a' <= 'a'", true),
+            ("'b' >= 'c'", false),
+        ];
+
+        for (input, expected) in &tests {
+            let evaluated = test_eval_helper(input.to_string());
+            test_bool_obj(evaluated.unwrap(), *expected);
+        }
+    }
+
+    #[test]
+    fn test_string_errors() {
+        let tests = vec![
+            ("'a' - 'b'", "unknown operator: STRING - STRING"),
+            ("'a' + 1", "type mismatch: STRING + INTEGER"),
+        ];
+
+        for (input, expected) in &tests {
+            match test_eval_helper(input.to_string()).unwrap() {
+                Object::Error(err) => assert_eq!(&err.message, expected),
+                obj => panic!("Object is not an error object. Got: {}", obj),
+            }
+        }
+    }
+
+    #[test]
+    fn test_comments_ignored() {
+        let input = "
+        let add = fn(x,y){
+        return x + y; /* the sum */
+        }
+        add(5,3); // we will get here 8.";
+        test_int_obj_helper(test_eval_helper(input.to_string()).unwrap(), 8);
+    }
+
+    #[test]
+    fn test_eval_float_expr() {
+        let tests = vec![
+            ("2.75", 2.75),
+            ("-2.5", -2.5),
+            (".5 + .25", 0.75),
+            ("1.5 * 2", 3.0),
+            ("2 * 1.5", 3.0),
+            ("1 / 4.0", 0.25),
+            ("10 - 0.5", 9.5),
+            ("1e-3 * 1000", 1.0),
+            (
+                "let ratio = fn(part, total){ part * 1.0 / total * 100 }; ratio(1, 8)",
+                12.5,
+            ),
+        ];
+
+        for (input, expected) in &tests {
+            match test_eval_helper(input.to_string()).unwrap() {
+                Object::Float(num) => assert_eq!(num, *expected, "input: {}", input),
+                obj => panic!("Object is not a Float. Got: {}", obj),
+            }
+        }
+    }
+
+    #[test]
+    fn test_float_comparison_and_display() {
+        let tests = vec![
+            ("1.5 < 2", true),
+            ("2 > 1.5", true),
+            ("1 == 1.0", true),
+            ("1.0 != 1", false),
+            ("0.1 + 0.2 == 0.3", false),
+            ("!0.0", true),
+            ("!-1.5", true),
+            ("!2.5", false),
+        ];
+        for (input, expected) in &tests {
+            let evaluated = test_eval_helper(input.to_string());
+            test_bool_obj(evaluated.unwrap(), *expected);
+        }
+
+        let tests = vec![
+            ("2.0", "2.0"),
+            ("1 / 2.0", "0.5"),
+            ("1e21", "1e21"),
+            ("7 / 2", "3"),
+        ];
+        for (input, expected) in &tests {
+            let evaluated = test_eval_helper(input.to_string());
+            assert_eq!(evaluated.unwrap().to_string(), *expected);
+        }
+
+        if let Object::Error(err) = test_eval_helper("1.5 / 0".to_string()).unwrap() {
+            assert_eq!(err.message, "division by zero");
+        } else {
+            panic!("Object is not an error object.");
+        }
+    }
+
+    #[test]
+    fn test_number_conversions() {
+        let tests = vec![
+            ("int(2.75)", 2),
+            ("int(-2.75)", -2),
+            ("int(0.5)", 0),
+            ("int(7)", 7),
+            ("int(1e3) + 1", 1001),
+            ("int(float(9007199254740993))", 9007199254740992),
+        ];
+        for (input, expected) in tests.iter() {
+            test_int_obj_helper(test_eval_helper(input.to_string()).unwrap(), *expected);
+        }
+        let evaluated = test_eval_helper("int(1e30)".to_string()).unwrap();
+        assert_eq!(evaluated.to_string(), "1000000000000000019884624838656");
+
+        let tests = vec![
+            ("float(3)", "3.0"),
+            ("float(-1) / 4", "-0.25"),
+            ("float(2.5)", "2.5"),
+            ("float(2 ** 70)", "1.1805916207174113e21"),
+            ("type(float(1))", "FLOAT"),
+            ("type(int(1.5))", "INTEGER"),
+        ];
+        for (input, expected) in tests.iter() {
+            let evaluated = test_eval_helper(input.to_string()).unwrap();
+            assert_eq!(evaluated.to_string(), *expected, "input: {}", input);
+        }
+
+        let tests = vec![
+            ("int(1e308 * 10)", "cannot convert inf to INTEGER"),
+            ("int(-1e308 * 10)", "cannot convert -inf to INTEGER"),
+            (
+                "int(1e308 * 10 - 1e308 * 10)",
+                "cannot convert NaN to INTEGER",
+            ),
+            ("float(2 ** 1024)", "integer is too large for FLOAT"),
+            ("int('1')", "argument to int not supported, got STRING"),
+            (
+                "float(true)",
+                "argument to float not supported, got BOOLEAN",
+            ),
+        ];
+        for (input, expected) in tests.iter() {
+            match test_eval_helper(input.to_string()).unwrap() {
+                Object::Error(err) => assert_eq!(err.message, *expected, "input: {}", input),
+                obj => panic!("Object is not an error. Got: {}", obj),
+            }
+        }
+    }
+
+    #[test]
+    fn test_int64_arithmetic() {
+        let tests = vec![
+            ("2147483647 + 1", 2147483648),
+            ("3000000000 * 3", 9000000000),
+            ("9223372036854775807", i64::MAX),
+            ("-9223372036854775807 - 1", i64::MIN),
+        ];
+
+        for (input, expected) in &tests {
+            test_int_obj_helper(test_eval_helper(input.to_string()).unwrap(), *expected);
+        }
+    }
+
+    #[test]
+    fn test_big_int_promotion() {
+        let tests = vec![
+            ("9223372036854775807 + 1", "9223372036854775808"),
+            ("-9223372036854775807 - 2", "-9223372036854775809"),
+            ("4611686018427387904 * 2", "9223372036854775808"),
+            (
+                "let min = -9223372036854775807 - 1; min / -1",
+                "9223372036854775808",
+            ),
+            (
+                "let min = -9223372036854775807 - 1; -min",
+                "9223372036854775808",
+            ),
+            (
+                "123456789012345678901234567890 * 1000",
+                "123456789012345678901234567890000",
+            ),
+            (
+                "let sq = fn(x){ x * x }; sq(sq(sq(65536)))",
+                "340282366920938463463374607431768211456",
+            ),
+        ];
+
+        for (input, expected) in &tests {
+            match test_eval_helper(input.to_string()).unwrap() {
+                Object::BigInteger(num) => assert_eq!(num.to_string(), *expected),
+                Object::Integer(num) => assert_eq!(num.to_string(), *expected),
+                obj => panic!("Object is not an integer. Got: {}", obj),
+            }
+        }
+    }
+
+    #[test]
+    fn test_big_int_demotion_and_comparison() {
+        let tests = vec![
+            ("9223372036854775808 - 1", i64::MAX),
+            ("(9223372036854775807 + 10) - 20", i64::MAX - 10),
+            ("-(9223372036854775808)", i64::MIN),
+            ("99999999999999999999 / 99999999999999999999", 1),
+            ("(9223372036854775807 * 4) / 4", i64::MAX),
+        ];
+        for (input, expected) in &tests {
+            test_int_obj_helper(test_eval_helper(input.to_string()).unwrap(), *expected);
+        }
+
+        let tests = vec![
+            ("9223372036854775808 > 9223372036854775807", true),
+            ("9223372036854775807 < 9223372036854775808", true),
+            ("9223372036854775807 + 1 == 9223372036854775808", true),
+            ("9223372036854775808 != 9223372036854775808", false),
+            ("9223372036854775808 > 1.5", true),
+            ("!9223372036854775808", false),
+            ("!-9223372036854775809", true),
+        ];
+        for (input, expected) in &tests {
+            let evaluated = test_eval_helper(input.to_string());
+            test_bool_obj(evaluated.unwrap(), *expected);
+        }
+
+        let tests = vec![
+            (
+                "9223372036854775808 + true",
+                "type mismatch: INTEGER + BOOLEAN",
+            ),
+            ("9223372036854775808 / 0", "division by zero"),
+        ];
+        for (input, expected) in &tests {
+            match test_eval_helper(input.to_string()).unwrap() {
+                Object::Error(err) => assert_eq!(&err.message, expected),
+                obj => panic!("Object is not an error object. Got: {}", obj),
+            }
+        }
+    }
+
+    #[test]
+    fn test_int_radix_literals() {
+        let tests = vec![
+            ("0xFF", 255),
+            ("0b1010 + 0o755", 503),
+            ("1_000_000 / 1_000", 1000),
+            ("-0x10", -16),
+        ];
+
+        for (input, expected) in tests.iter() {
+            test_int_obj_helper(test_eval_helper(input.to_string()).unwrap(), *expected);
+        }
+        match test_eval_helper("0xFFFFFFFFFFFFFFFF".to_string()).unwrap() {
+            Object::BigInteger(num) => assert_eq!(num.to_string(), "18446744073709551615"),
+            obj => panic!("Object is not a big integer. Got: {}", obj),
+        }
+    }
+
+    #[test]
+    fn test_unicode_identifiers() {
+        let input = "let _tmp = 5; let café = _tmp * 2; let 合計 = café + 1; 合計";
+        test_int_obj_helper(test_eval_helper(input.to_string()).unwrap(), 11);
+    }
+
+    #[test]
+    fn test_unary_plus() {
+        test_int_obj_helper(test_eval_helper("+5 - -3".to_string()).unwrap(), 8);
+        match test_eval_helper("+2.5".to_string()).unwrap() {
+            Object::Float(num) => assert_eq!(num, 2.5),
+            obj => panic!("Object is not a float. Got: {}", obj),
+        }
+        match test_eval_helper("+true".to_string()).unwrap() {
+            Object::Error(err) => assert_eq!(err.message, "unknown operator: +BOOLEAN"),
+            obj => panic!("Object is not an error. Got: {}", obj),
+        }
+    }
+
+    #[test]
+    fn test_logical_operators() {
+        let tests = vec![
+            ("true && true", true),
+            ("true && false", false),
+            ("false || true", true),
+            ("false || false", false),
+            ("1 < 2 && 2 < 3", true),
+            ("1 && 2.5", true),
+            ("0 || -1", false),
+            ("'a' == 'a' || false", true),
+            ("false && true || true", true),
+        ];
+
+        for (input, expected) in &tests {
+            let evaluated = test_eval_helper(input.to_string());
+            test_bool_obj(evaluated.unwrap(), *expected);
+        }
+    }
+
+    #[test]
+    fn test_logical_short_circuit() {
+        let tests = vec![
+            ("false && undefined_name", false),
+            ("true || (1 + true)", true),
+            ("let f = fn() { return 1 + true; }; 0 && f()", false),
+        ];
+
+        for (input, expected) in &tests {
+            let evaluated = test_eval_helper(input.to_string());
+            test_bool_obj(evaluated.unwrap(), *expected);
+        }
+        match test_eval_helper("true && undefined_name".to_string()).unwrap() {
+            Object::Error(err) => assert_eq!(err.message, "identifier not found: undefined_name"),
+            obj => panic!("Object is not an error. Got: {}", obj),
+        }
+    }
+
+    #[test]
+    fn test_modulo_and_exponent() {
+        let tests = vec![
+            ("7 % 3", 1),
+            ("-7 % 3", -1),
+            ("2 ** 10", 1024),
+            ("2 ** 3 ** 2", 512),
+            ("-2 ** 2", -4),
+            ("(-2) ** 3", -8),
+            ("10 % 4 ** 2", 10),
+            //bases that don't grow take any exponent.
+            ("0 ** 0", 1),
+            ("0 ** 99999999999", 0),
+            ("1 ** 99999999999", 1),
+            ("(-1) ** 99999999999", -1),
+            ("(-1) ** 99999999998", 1),
+            ("0 << 4000000000", 0),
+        ];
+        for (input, expected) in tests.iter() {
+            test_int_obj_helper(test_eval_helper(input.to_string()).unwrap(), *expected);
+        }
+
+        match test_eval_helper("2 ** 64".to_string()).unwrap() {
+            Object::BigInteger(num) => assert_eq!(num.to_string(), "18446744073709551616"),
+            obj => panic!("Object is not a big integer. Got: {}", obj),
+        }
+        //large results within MAX_INT_BITS are still computed.
+        match test_eval_helper("2 ** 500000".to_string()).unwrap() {
+            Object::BigInteger(num) => assert_eq!(num.bits(), 500001),
+            obj => panic!("Object is not a big integer. Got: {}", obj),
+        }
+        let float_tests = vec![("2 ** -1", 0.5), ("7.5 % 2", 1.5), ("4.0 ** 0.5", 2.0)];
+        for (input, expected) in float_tests.iter() {
+            match test_eval_helper(input.to_string()).unwrap() {
+                Object::Float(num) => assert_eq!(num, *expected, "input: {}", input),
+                obj => panic!("Object is not a float. Got: {}", obj),
+            }
+        }
+    }
+
+    #[test]
+    fn test_bitwise_operators() {
+        let tests = vec![
+            ("6 & 3", 2),
+            ("6 | 3", 7),
+            ("6 ^ 3", 5),
+            ("~5", -6),
+            ("1 << 10", 1024),
+            ("1024 >> 3", 128),
+            ("-16 >> 2", -4),
+            ("1 >> 100", 0),
+            ("-1 >> 100", -1),
+            ("(1 << 70) >> 68", 4),
+            ("1 | 2 ^ 3 & 4", 3),
+        ];
+        for (input, expected) in tests.iter() {
+            test_int_obj_helper(test_eval_helper(input.to_string()).unwrap(), *expected);
+        }
+    }
+
+    #[test]
+    fn test_operator_type_errors() {
+        let tests = vec![
+            ("5 % 0", "division by zero"),
+            ("1.5 % 0", "division by zero"),
+            ("1 << -1", "negative shift amount"),
+            ("3 ** 4000000000", "exponent is too large"),
+            ("(2 ** 1000) ** 2000", "exponent is too large"),
+            ("1 << 4000000000", "shift amount is too large"),
+            ("(1 << 1048576) << 1", "shift amount is too large"),
+            ("true % false", "unknown operator: BOOLEAN % BOOLEAN"),
+            ("true ** 2", "type mismatch: BOOLEAN ** INTEGER"),
+            ("true & false", "unknown operator: BOOLEAN & BOOLEAN"),
+            ("1 | true", "unknown operator: INTEGER | BOOLEAN"),
+            ("1.5 << 1", "unknown operator: FLOAT << INTEGER"),
+            ("fn(x) { x } ^ 1", "unknown operator: FUNCTION ^ INTEGER"),
+            ("~true", "unknown operator: ~BOOLEAN"),
+            ("~1.5", "unknown operator: ~FLOAT"),
+        ];
+        for (input, expected) in tests.iter() {
+            match test_eval_helper(input.to_string()).unwrap() {
+                Object::Error(err) => assert_eq!(err.message, *expected, "input: {}", input),
+                obj => panic!("Object is not an error. Got: {}", obj),
+            }
+        }
+    }
+
+    #[test]
+    fn test_while_loops() {
+        let tests = vec![
+            (
+                "let i = 0; let sum = 0; while (i < 5) { let i = i + 1; let sum = sum + i; } sum",
+                15,
+            ),
+            (
+                "let i = 0; while (true) { let i = i + 1; if (i == 10) { break; } } i",
+                10,
+            ),
+            (
+                "let i = 0; let sum = 0; while (i < 5) { let i = i + 1; if (i == 2) { continue; } let sum = sum + i; } sum",
+                13,
+            ),
+            (
+                "let f = fn() { let i = 0; while (true) { let i = i + 1; if (i > 3) { return i; } } }; f()",
+                4,
+            ),
+            (
+                "let n = 0; let i = 0; while (i < 3) { let i = i + 1; let j = 0; while (true) { let j = j + 1; let n = n + 1; if (j == 2) { break; } } } n",
+                6,
+            ),
+        ];
+        for (input, expected) in tests.iter() {
+            test_int_obj_helper(test_eval_helper(input.to_string()).unwrap(), *expected);
+        }
+
+        //the README example, assignment and let in a while body both reach the outer scope.
+        let tests = vec![
+            (
+                "let i = 0; let n = 0; while (i < 10) { i += 1; if (i == 5) { continue; } n += 1; } n",
+                9,
+            ),
+            ("let i = 0; while (i < 3) { let x = i; i += 1; } x", 2),
+        ];
+        for (input, expected) in tests.iter() {
+            test_int_obj_helper(test_eval_helper(input.to_string()).unwrap(), *expected);
+        }
+        match test_eval_helper("for (i in range(0, 3)) { let x = i; } x".to_string()).unwrap() {
+            Object::Error(err) => assert_eq!(err.message, "identifier not found: x"),
+            obj => panic!("Object is not an error. Got: {}", obj),
+        }
+
+        test_null_obj(test_eval_helper("while (false) { 1 }".to_string()).unwrap());
+        match test_eval_helper("while (1 + true) { 1 }".to_string()).unwrap() {
+            Object::Error(err) => assert_eq!(err.message, "type mismatch: INTEGER + BOOLEAN"),
+            obj => panic!("Object is not an error. Got: {}", obj),
+        }
+    }
+
+    #[test]
+    fn test_loop_signals_in_expressions() {
+        //a break, continue or return inside an expression ends the whole statment, the value
+        //it stopped is never bound or stored.
+        let tests = vec![
+            (
+                "let i = 0; let y = 0; while (true) { let y = if (i > 2) { break; } else { i }; i += 1; } y",
+                2,
+            ),
+            (
+                "let i = 0; let n = 0; while (i < 5) { i += 1; let a = [if (i == 2) { continue } else { i }]; n += a[0]; } n",
+                13,
+            ),
+            (
+                "let n = 0; for (i in range(0, 5)) { n += i + if (i == 3) { break } else { 0 }; } n",
+                3,
+            ),
+            (
+                "let n = 0; for (i in range(0, 3)) { let h = {'k': if (i == 1) { continue } else { i }}; n += h['k'] + 1; } n",
+                4,
+            ),
+            (
+                "let n = 0; for (i in range(0, 3)) { puts(if (i == 1) { break } else { i }); n += 1; } n",
+                1,
+            ),
+            (
+                "let f = fn(c) { let x = if (c) { return 1; } else { 2 }; x * 10 }; f(true) + f(false)",
+                21,
+            ),
+            (
+                "let f = fn() { [1, if (true) { return 5; } else { 0 }, 3] }; f()",
+                5,
+            ),
+            (
+                "let f = fn() { while (true) { return if (true) { break } else { 1 }; } 7 }; f()",
+                7,
+            ),
+        ];
+        for (input, expected) in tests.iter() {
+            test_int_obj_helper(test_eval_helper(input.to_string()).unwrap(), *expected);
+        }
+    }
+
+    #[test]
+    fn test_for_loops() {
+        let tests = vec![
+            (
+                "let f = fn() { for (x in range(0, 10)) { if (x * x > 20) { return x; } } }; f()",
+                5,
+            ),
+            (
+                "let f = fn() { for (x in range(10, 0, -3)) { if (x < 5) { return x; } } }; f()",
+                4,
+            ),
+            (
+                "let f = fn() { for (x in range(0, 10)) { if (x < 3) { continue; } return x; } }; f()",
+                3,
+            ),
+            (
+                "let f = fn(limit) { for (x in range(0, 10)) { if (x == limit) { return x; } } }; f(7)",
+                7,
+            ),
+            (
+                "let f = fn() { let base = 10; for (x in range(0, 5)) { if (x == 3) { return base + x; } } }; f()",
+                13,
+            ),
+            //a let in the body binds in that iteration's scope only.
+            ("let x = 7; for (i in range(0, 3)) { let x = i; } x", 7),
+            //every iteration binds a new x, the closure keeps the one it was created with.
+            (
+                "let f = fn() { for (x in range(0, 3)) { let g = fn() { x }; if (x == 1) { return g; } } }; f()()",
+                1,
+            ),
+            //range is a builtin value, a user binding shadows it.
+            ("let f = fn(r) { for (i in r(5, 9)) { return i; } }; f(range)", 5),
+            ("let range = fn(a, b) { a + b }; range(2, 3)", 5),
+        ];
+        for (input, expected) in tests.iter() {
+            test_int_obj_helper(test_eval_helper(input.to_string()).unwrap(), *expected);
+        }
+
+        let input = "let f = fn() { for (c in 'héllo') { if (c != 'h') { return c; } } }; f()";
+        match test_eval_helper(input.to_string()).unwrap() {
+            Object::String(s) => assert_eq!(s, "é"),
+            obj => panic!("Object is not a string. Got: {}", obj),
+        }
+        test_null_obj(test_eval_helper("for (x in range(0, 5)) { break; }".to_string()).unwrap());
+        test_null_obj(test_eval_helper("for (x in range(5, 0)) { x }".to_string()).unwrap());
+    }
+
+    #[test]
+    fn test_for_loop_errors() {
+        let tests = vec![
+            ("for (x in 5) { x }", "INTEGER is not iterable"),
+            ("for (x in range(0, 3)) { x } x", "identifier not found: x"),
+            ("range(0, 10, 0)", "range step cannot be zero"),
+            (
+                "range(1)",
+                "wrong number of arguments to range. got=1, want=2 or 3",
+            ),
+            (
+                "range(0, 'a')",
+                "range arguments must be INTEGER, got STRING",
+            ),
+            (
+                "range(0, 2 ** 70)",
+                "range argument is too large: 1180591620717411303424",
+            ),
+            (
+                "range(-(2 ** 64), 0)",
+                "range argument is too large: -18446744073709551616",
+            ),
+            (
+                "for (x in range(0, 3)) { x + true }",
+                "type mismatch: INTEGER + BOOLEAN",
+            ),
+        ];
+        for (input, expected) in tests.iter() {
+            match test_eval_helper(input.to_string()).unwrap() {
+                Object::Error(err) => assert_eq!(err.message, *expected, "input: {}", input),
+                obj => panic!("Object is not an error. Got: {}", obj),
+            }
+        }
+        match test_eval_helper("range".to_string()).unwrap() {
+            obj @ Object::Builtin(_) => assert_eq!(obj.to_string(), "builtin function range"),
+            obj => panic!("Object is not a builtin. Got: {}", obj),
+        }
+        match test_eval_helper("range(0, 10, 2)".to_string()).unwrap() {
+            obj @ Object::Range { .. } => assert_eq!(obj.to_string(), "range(0, 10, 2)"),
+            obj => panic!("Object is not a range. Got: {}", obj),
+        }
+    }
+
+    #[test]
+    fn test_assignment() {
+        let tests = vec![
+            ("let x = 1; x = 5; x", 5),
+            ("let x = 1; x = x + 1", 2),
+            ("let x = 10; x += 5; x -= 3; x *= 2; x /= 4; x %= 4; x", 2),
+            ("let a = 1; let b = 2; a = b = 7; a + b", 14),
+            ("let i = 0; let sum = 0; while (i < 5) { i += 1; sum += i; } sum", 15),
+            (
+                "let f = fn(n) { let total = 0; while (n > 0) { total += n; n -= 1; } total }; f(4)",
+                10,
+            ),
+            //assignment updates the binding in the enclosing scope that owns it.
+            ("let sum = 0; for (x in range(0, 4)) { sum += x } sum", 6),
+            ("let n = 0; let inc = fn() { n = n + 1 }; inc(); inc(); n", 2),
+            (
+                "let f = fn() { let total = 0; for (x in range(1, 4)) { total += x; } total }; f()",
+                6,
+            ),
+            //a let in a body binds a new variable, which shadows the outer one.
+            ("let x = 1; let f = fn() { let x = 5; x = 6; x }; f() + x", 7),
+        ];
+        for (input, expected) in tests.iter() {
+            test_int_obj_helper(test_eval_helper(input.to_string()).unwrap(), *expected);
+        }
+
+        match test_eval_helper("let s = 'a'; s += 'b'; s".to_string()).unwrap() {
+            Object::String(s) => assert_eq!(s, "ab"),
+            obj => panic!("Object is not a string. Got: {}", obj),
+        }
+    }
+
+    #[test]
+    fn test_assignment_errors() {
+        let tests = vec![
+            ("y = 5", "cannot assign to undeclared identifier: y"),
+            ("y += 5", "identifier not found: y"),
+            ("let x = 1; x += true", "type mismatch: INTEGER + BOOLEAN"),
+            ("let x = 1; x /= 0", "division by zero"),
+            ("let x = 1; x = y", "identifier not found: y"),
+        ];
+        for (input, expected) in tests.iter() {
+            match test_eval_helper(input.to_string()).unwrap() {
+                Object::Error(err) => assert_eq!(err.message, *expected, "input: {}", input),
+                obj => panic!("Object is not an error. Got: {}", obj),
+            }
+        }
+    }
+
+    #[test]
+    fn test_array_literals_and_indexing() {
+        let tests = vec![
+            ("[1, 2, 3][0]", 1),
+            ("[1, 2, 3][2]", 3),
+            ("let i = 0; [1][i]", 1),
+            ("[1, 2, 3][1 + 1]", 3),
+            ("let arr = [1, 2, 3]; arr[2]", 3),
+            ("let arr = [1, 2, 3]; arr[0] + arr[1] + arr[2]", 6),
+            ("[1, 2, 3][-1]", 3),
+            ("[1, 2, 3][-3]", 1),
+            ("[[1, 2], [3, 4]][1][0]", 3),
+        ];
+        for (input, expected) in tests.iter() {
+            test_int_obj_helper(test_eval_helper(input.to_string()).unwrap(), *expected);
+        }
+
+        for input in [
+            "[1, 2, 3][3]",
+            "[1, 2, 3][-4]",
+            "[][0]",
+            "[1][99999999999999999999]",
+        ] {
+            test_null_obj(test_eval_helper(input.to_string()).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_array_display_and_equality() {
+        let display_tests = vec![
+            ("[1, 2 * 2, 3 + 3]", "[1, 4, 6]"),
+            ("[]", "[]"),
+            (
+                "[[1, 'a'], [true, [2.5]], '1']",
+                "[[1, \"a\"], [true, [2.5]], \"1\"]",
+            ),
+        ];
+        for (input, expected) in display_tests.iter() {
+            let evaluated = test_eval_helper(input.to_string()).unwrap();
+            assert_eq!(evaluated.to_string(), *expected);
+        }
+
+        let eq_tests = vec![
+            ("[1, 2] == [1, 2]", true),
+            ("[1, 2] == [2, 1]", false),
+            ("[[1], 'a'] == [[1], 'a']", true),
+            ("[1, 2] != [1, 2, 3]", true),
+        ];
+        for (input, expected) in &eq_tests {
+            test_bool_obj(test_eval_helper(input.to_string()).unwrap(), *expected);
+        }
+    }
+
+    #[test]
+    fn test_array_errors_and_iteration() {
+        let tests = vec![
+            ("[1, 2]['a']", "index must be an INTEGER, got STRING"),
+            ("5[0]", "index operator not supported: INTEGER"),
+            ("[1, 2 + true]", "type mismatch: INTEGER + BOOLEAN"),
+            ("[1] == 1", "type mismatch: ARRAY == INTEGER"),
+        ];
+        for (input, expected) in tests.iter() {
+            match test_eval_helper(input.to_string()).unwrap() {
+                Object::Error(err) => assert_eq!(err.message, *expected, "input: {}", input),
+                obj => panic!("Object is not an error. Got: {}", obj),
+            }
+        }
+
+        let input = "let total = 0; let arr = [1, 2, 3]; let i = 0; while (i < 3) { total += arr[i]; i += 1; } total";
+        test_int_obj_helper(test_eval_helper(input.to_string()).unwrap(), 6);
+        let input =
+            "let f = fn(arr) { for (x in arr) { if (x > 1) { return x; } } }; f([0, 1, 5, 7])";
+        test_int_obj_helper(test_eval_helper(input.to_string()).unwrap(), 5);
+    }
+
+    #[test]
+    fn test_hash_literals_and_indexing() {
+        let input = "let two = 'two'; let h = {'one': 10 - 9, two: 1 + 1, 'thr' + 'ee': 6 / 2, 4: 4, true: 5, false: 6}; h";
+        let hash = match test_eval_helper(input.to_string()).unwrap() {
+            Object::Hash(hash) => hash,
+            obj => panic!("Object is not a hash. Got: {}", obj),
+        };
+        assert_eq!(hash.pairs().len(), 6);
+        let expected = vec![
+            (HashKey::String("one".to_string()), 1),
+            (HashKey::String("two".to_string()), 2),
+            (HashKey::String("three".to_string()), 3),
+            (HashKey::Integer(4), 4),
+            (HashKey::Boolean(true), 5),
+            (HashKey::Boolean(false), 6),
+        ];
+        for (key, value) in expected {
+            test_int_obj_helper(hash.get(&key).unwrap().clone(), value);
+        }
+
+        let tests = vec![
+            ("{'foo': 5}['foo']", 5),
+            ("let key = 'foo'; {'foo': 5}[key]", 5),
+            ("{5: 5}[5]", 5),
+            ("{true: 5}[true]", 5),
+            ("{99999999999999999999: 1}[99999999999999999999]", 1),
+            ("{'a': 1, 'a': 2}['a']", 2),
+            ("{'a': {'b': 7}}['a']['b']", 7),
+        ];
+        for (input, expected) in tests.iter() {
+            test_int_obj_helper(test_eval_helper(input.to_string()).unwrap(), *expected);
+        }
+        test_null_obj(test_eval_helper("{'foo': 5}['bar']".to_string()).unwrap());
+        test_null_obj(test_eval_helper("{}['foo']".to_string()).unwrap());
+    }
+
+    #[test]
+    fn test_hash_display_equality_and_errors() {
+        let evaluated = test_eval_helper("{'b': 1, 2: [true, 'x'], 'a': {}, 'b': 3}".to_string());
+        assert_eq!(
+            evaluated.unwrap().to_string(),
+            "{\"b\": 3, 2: [true, \"x\"], \"a\": {}}"
+        );
+
+        let eq_tests = vec![
+            ("{'a': 1, 'b': 2} == {'b': 2, 'a': 1}", true),
+            ("{'a': 1} == {'a': 2}", false),
+            ("{'a': 1} != {'a': 1, 'b': 2}", true),
+        ];
+        for (input, expected) in &eq_tests {
+            test_bool_obj(test_eval_helper(input.to_string()).unwrap(), *expected);
+        }
+
+        let tests = vec![
+            ("{fn(x) { x }: 1}", "unusable as hash key: FUNCTION"),
+            ("{1.5: 1}", "unusable as hash key: FLOAT"),
+            ("{'a': 1}[[1]]", "unusable as hash key: ARRAY"),
+            ("{'a': 1 + true}", "type mismatch: INTEGER + BOOLEAN"),
+        ];
+        for (input, expected) in tests.iter() {
+            match test_eval_helper(input.to_string()).unwrap() {
+                Object::Error(err) => assert_eq!(err.message, *expected, "input: {}", input),
+                obj => panic!("Object is not an error. Got: {}", obj),
+            }
+        }
+
+        let input = "let f = fn(h) { for (k in h) { if (h[k] > 1) { return k; } } }; f({'x': 1, 'y': 2, 'z': 3})";
+        match test_eval_helper(input.to_string()).unwrap() {
+            Object::String(s) => assert_eq!(s, "y"),
+            obj => panic!("Object is not a string. Got: {}", obj),
+        }
+    }
+
+    #[test]
+    fn test_builtin_functions() {
+        let tests = vec![
+            ("len('')", 0),
+            ("len('four')", 4),
+            ("len('héllo')", 5),
+            ("len([1, 2, 3])", 3),
+            ("len({'a': 1, 'b': 2})", 2),
+            ("first([1, 2, 3])", 1),
+            ("last([1, 2, 3])", 3),
+            ("len(rest([1, 2, 3]))", 2),
+            ("rest([1, 2, 3])[0]", 2),
+            ("last(push([1, 2], 3))", 3),
+            ("let a = [1]; let b = push(a, 2); len(a) + len(b)", 3),
+            ("let len = fn(x) { 42 }; len('abc')", 42),
+            ("let f = fn(g) { g([1, 2]) }; f(len)", 2),
+        ];
+        for (input, expected) in tests.iter() {
+            test_int_obj_helper(test_eval_helper(input.to_string()).unwrap(), *expected);
+        }
+
+        for input in ["first([])", "last([])", "rest([])", "puts('hello', 1)"] {
+            test_null_obj(test_eval_helper(input.to_string()).unwrap());
+        }
+        let type_tests = vec![
+            ("type(1)", "INTEGER"),
+            ("type('a')", "STRING"),
+            ("type([])", "ARRAY"),
+            ("type({})", "HASH"),
+            ("type(fn() {})", "FUNCTION"),
+            ("type(len)", "BUILTIN"),
+        ];
+        for (input, expected) in type_tests.iter() {
+            match test_eval_helper(input.to_string()).unwrap() {
+                Object::String(s) => assert_eq!(s, *expected, "input: {}", input),
+                obj => panic!("Object is not a string. Got: {}", obj),
+            }
+        }
+        let evaluated = test_eval_helper("len".to_string()).unwrap();
+        assert_eq!(evaluated.to_string(), "builtin function len");
+        test_bool_obj(test_eval_helper("len == len".to_string()).unwrap(), true);
+        test_bool_obj(test_eval_helper("len == first".to_string()).unwrap(), false);
+    }
+
+    #[test]
+    fn test_builtin_and_call_errors() {
+        let tests = vec![
+            ("len(1)", "argument to len not supported, got INTEGER"),
+            (
+                "first('abc')",
+                "argument to first not supported, got STRING",
+            ),
+            ("push(1, 1)", "argument to push not supported, got INTEGER"),
+            (
+                "len('one', 'two')",
+                "wrong number of arguments to len. got=2, want=1",
+            ),
+            ("len()", "wrong number of arguments to len. got=0, want=1"),
+            (
+                "push([])",
+                "wrong number of arguments to push. got=1, want=2",
+            ),
+            (
+                "range(1, 2, 3, 4)",
+                "wrong number of arguments to range. got=4, want=2 or 3",
+            ),
+            (
+                "fn(x, y) { x }(1)",
+                "wrong number of arguments. got=1, want=2",
+            ),
+            (
+                "fn() { 1 }(1, 2)",
+                "wrong number of arguments. got=2, want=0",
+            ),
+            ("len(1 + true)", "type mismatch: INTEGER + BOOLEAN"),
+        ];
+        for (input, expected) in tests.iter() {
+            match test_eval_helper(input.to_string()).unwrap() {
+                Object::Error(err) => assert_eq!(err.message, *expected, "input: {}", input),
+                obj => panic!("Object is not an error. Got: {}", obj),
+            }
+        }
+    }
+
+    #[test]
+    fn test_recursion_and_closures() {
+        let tests = vec![
+            (
+                "let fib = fn(n) { if (n < 2) { return n; } fib(n - 1) + fib(n - 2) }; fib(15)",
+                610,
+            ),
+            (
+                "let is_even = fn(n) { if (n == 0) { true } else { is_odd(n - 1) } };
+                 let is_odd = fn(n) { if (n == 0) { false } else { is_even(n - 1) } };
+                 if (is_even(10) && is_odd(7)) { 1 } else { 0 }",
+                1,
+            ),
+            (
+                "let new_adder = fn(x) { fn(y) { x + y } }; let add_two = new_adder(2); add_two(3)",
+                5,
+            ),
+            (
+                "let counter = fn() { let count = 0; fn() { count += 1; count } };
+                 let c = counter(); c(); c(); c()",
+                3,
+            ),
+            (
+                "let counter = fn() { let count = 0; fn() { count += 1; count } };
+                 let a = counter(); let b = counter(); a(); a(); b()",
+                1,
+            ),
+            ("let x = 1; let f = fn() { x }; x = 5; f()", 5),
+            ("let total = 0; let add = fn(n) { total += n; }; add(2); add(3); total", 5),
+            ("let f = fn() { y }; let y = 7; f()", 7),
+            ("let x = 1; let f = fn(x) { x = 10; x }; f(2) + x", 11),
+            ("let sum = 0; for (i in range(1, 5)) { sum += i; } sum", 10),
+            (
+                "let fs = []; for (i in range(0, 3)) { fs = push(fs, fn() { i * 10 }); } fs[0]() + fs[2]()",
+                20,
+            ),
+        ];
+        for (input, expected) in tests.iter() {
+            test_int_obj_helper(test_eval_helper(input.to_string()).unwrap(), *expected);
+        }
+    }
+
+    #[test]
+    fn test_scope_cycles_are_freed() {
+        //h is stored in the call scope it captures, every call of g used to leave that scope
+        //behind. Scopes are counted per thread, so other tests don't affect the count.
+        let input = "let g = fn(n) { let h = fn(x) { x + n }; h(1) };
+                     let total = 0; for (i in range(0, 10000)) { total += g(i); } total";
+        test_int_obj_helper(test_eval_helper(input.to_string()).unwrap(), 50_005_000);
+        assert!(Enviornment::live_scopes() < 3000);
+        //the top level scope holds g, which captures it, nothing is left once it is collected.
+        Enviornment::collect_cycles();
+        assert_eq!(Enviornment::live_scopes(), 0);
+
+        //scopes still reachable from outside survive collections, closures keep their state.
+        let input = "let counter = fn() { let count = 0; fn() { count += 1; count } };
+                     let c = counter(); let f = fn() { let g = fn() { 1 }; g() };
+                     for (i in range(0, 5000)) { f(); } c(); c(); c()";
+        test_int_obj_helper(test_eval_helper(input.to_string()).unwrap(), 3);
+    }
+
+    #[test]
+    fn test_tail_calls() {
+        //each of these recurses past the default call depth, so only runs as tail calls.
+        let tests = vec![
+            (
+                "let count = fn(n, acc) { if (n == 0) { acc } else { count(n - 1, acc + 1) } }; count(20000, 0)",
+                20000,
+            ),
+            (
+                "let f = fn(n) { if (n > 0) { return f(n - 1); } 42 }; f(20000)",
+                42,
+            ),
+            (
+                "let f = fn(n) { if (n == 0) { 7 } else if (n % 2 == 0) { f(n - 1) } else { f(n - 1) } }; f(20000)",
+                7,
+            ),
+            (
+                "let is_even = fn(n) { if (n == 0) { true } else { is_odd(n - 1) } };
+                 let is_odd = fn(n) { if (n == 0) { false } else { is_even(n - 1) } };
+                 if (is_even(20000)) { 1 } else { 0 }",
+                1,
+            ),
+            ("let f = fn(a) { len(a) }; f([1, 2])", 2),
+        ];
+        for (input, expected) in tests.iter() {
+            test_int_obj_helper(test_eval_helper(input.to_string()).unwrap(), *expected);
+        }
+        //a top level return runs its tail call before handing the value back.
+        match test_eval_helper("let f = fn(x) { x * 2 }; return f(21);".to_string()).unwrap() {
+            Object::ReturnValue(val) => test_int_obj_helper(*val, 42),
+            obj => panic!("Object is not a return value. Got: {}", obj),
+        }
+    }
+
+    #[test]
+    fn test_call_depth_limit() {
+        //the limit is per thread, so lowering it here doesn't affect other tests. It is low
+        //enough to be reached before the default stack limit of the test thread.
+        set_max_call_depth(30);
+        let tests = vec![
+            (
+                "let f = fn(n) { 1 + f(n + 1) }; f(0)",
+                "stack overflow: maximum call depth of 30 exceeded",
+            ),
+            (
+                "let f = fn(n) { if (n == 0) { 0 } else { 1 + f(n - 1) } }; f(60)",
+                "stack overflow: maximum call depth of 30 exceeded",
+            ),
+            ("let f = fn() { g() }; f()", "identifier not found: g"),
+        ];
+        for (input, expected) in tests.iter() {
+            match test_eval_helper(input.to_string()).unwrap() {
+                Object::Error(err) => assert_eq!(err.message, *expected, "input: {}", input),
+                obj => panic!("Object is not an error. Got: {}", obj),
+            }
+        }
+        //the depth unwinds with the error, so shallower recursion still works afterwards.
+        test_int_obj_helper(
+            test_eval_helper(
+                "let f = fn(n) { if (n == 0) { 0 } else { 1 + f(n - 1) } }; f(20)".to_string(),
+            )
+            .unwrap(),
+            20,
+        );
+        set_max_call_depth(DEFAULT_MAX_CALL_DEPTH);
+    }
+
+    #[test]
+    fn test_stack_limit_nested_body() {
+        //each call of f nests 30 expressions, so the native stack runs out long before the
+        //call depth limit is reached.
+        let body = format!("{}f(n + 1){}", "1 + (".repeat(30), ")".repeat(30));
+        let input = format!("let f = fn(n) {{ {} }}; f(0)", body);
+        let stack_size = 16 * 1024 * 1024;
+        //objects can't leave the thread, so it does its own asserts and the join reports them.
+        std::thread::Builder::new()
+            .stack_size(stack_size)
+            .spawn(move || {
+                set_stack_size(stack_size);
+                match test_eval_helper(input).unwrap() {
+                    Object::Error(err) => {
+                        assert_eq!(err.kind, ErrorKind::StackOverflow);
+                        assert_eq!(err.message, "stack overflow: evaluation nested too deeply");
+                    }
+                    obj => panic!("Object is not an error. Got: {}", obj),
+                }
+                //a shallow call on the same thread still works after the error unwinds.
+                test_int_obj_helper(
+                    test_eval_helper(
+                        "let f = fn(n) { if (n == 0) { 0 } else { 1 + f(n - 1) } }; f(50)"
+                            .to_string(),
+                    )
+                    .unwrap(),
+                    50,
+                );
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+    }
+
+    #[test]
+    fn test_stack_limit_default() {
+        //without set_stack_size the first eval assumes a small stack, deep recursion on the 2MB
+        //test thread is an error instead of a crash.
+        let input = "let f = fn(n) { if (n == 0) { 0 } else { 1 + f(n - 1) } }; f(9000)";
+        match test_eval_helper(input.to_string()).unwrap() {
+            Object::Error(err) => {
+                assert_eq!(err.kind, ErrorKind::StackOverflow);
+                assert_eq!(err.message, "stack overflow: evaluation nested too deeply");
+            }
+            obj => panic!("Object is not an error. Got: {}", obj),
+        }
+    }
+
+    #[test]
+    fn test_runtime_error_kinds_and_spans() {
+        let tests = vec![
+            ("1 + true", ErrorKind::TypeMismatch, "1:1"),
+            ("let x = 5;\nx + -false", ErrorKind::UnknownOperator, "2:5"),
+            ("missing", ErrorKind::UndefinedIdentifier, "1:1"),
+            ("[1, 2, 10 / 0]", ErrorKind::DivisionByZero, "1:8"),
+            ("let x = 1; x()", ErrorKind::NotCallable, "1:12"),
+            ("len(1, 2)", ErrorKind::ArityMismatch, "1:1"),
+            ("first(1)", ErrorKind::InvalidArgument, "1:1"),
+            ("[1][true]", ErrorKind::InvalidIndex, "1:1"),
+            ("for (x in 5) { x }", ErrorKind::NotIterable, "1:1"),
+            ("2 ** 9223372036854775808", ErrorKind::OutOfRange, "1:1"),
+        ];
+        for (input, kind, span) in tests.iter() {
+            match test_eval_helper(input.to_string()).unwrap() {
+                Object::Error(err) => {
+                    assert_eq!(err.kind, *kind, "input: {}", input);
+                    assert_eq!(err.span.unwrap().to_string(), *span, "input: {}", input);
+                }
+                obj => panic!("Object is not an error. Got: {}", obj),
+            }
+        }
+    }
+
+    #[test]
+    fn test_runtime_error_traceback() {
+        let input = "let inner = fn(x) { x + true };
+let outer = fn(x) { 1 + inner(x) };
+outer(1)";
+        match test_eval_helper(input.to_string()).unwrap() {
+            Object::Error(err) => {
+                let names: Vec<&str> = err.frames.iter().map(|f| f.name.as_str()).collect();
+                assert_eq!(names, vec!["inner", "outer"]);
+                assert_eq!(
+                    err.traceback(),
+                    "Traceback (most recent call last):
+  outer called at 3:1
+  inner called at 2:25
+Error: type mismatch: INTEGER + BOOLEAN at 1:21"
+                );
+                assert_eq!(
+                    err.traceback_in("main.qk"),
+                    "Traceback (most recent call last):
+  outer called at main.qk:3:1
+  inner called at main.qk:2:25
+Error: type mismatch: INTEGER + BOOLEAN at main.qk:1:21"
+                );
+            }
+            obj => panic!("Object is not an error. Got: {}", obj),
+        }
+
+        //errors outside any function have no frames, anonymous functions get a placeholder name.
+        let tests = vec![
+            ("1 / 0", "Error: division by zero at 1:1"),
+            ("return 1 / 0;", "Error: division by zero at 1:8"),
+            (
+                "fn() { -true }()",
+                "Traceback (most recent call last):
+  <anonymous> called at 1:1
+Error: unknown operator: -BOOLEAN at 1:8",
+            ),
+        ];
+        for (input, expected) in tests.iter() {
+            match test_eval_helper(input.to_string()).unwrap() {
+                Object::Error(err) => assert_eq!(err.traceback(), *expected),
+                obj => panic!("Object is not an error. Got: {}", obj),
+            }
+        }
+    }
+
+    #[test]
+    fn test_traceback_repeated_frames() {
+        let input = "let f = fn(n) { if (n == 0) { 1 + true } else { 1 + f(n - 1) } }; f(50)";
+        match test_eval_helper(input.to_string()).unwrap() {
+            Object::Error(err) => {
+                assert_eq!(err.frames.len(), 51);
+                assert_eq!(
+                    err.traceback(),
+                    "Traceback (most recent call last):
+  f called at 1:67
+  f called at 1:53
+  f called at 1:53
+  f called at 1:53
+  [previous line repeated 47 more times]
+Error: type mismatch: INTEGER + BOOLEAN at 1:31"
+                );
+            }
+            obj => panic!("Object is not an error. Got: {}", obj),
+        }
+
+        //alternating calls don't repeat, the middle of the traceback is left out instead.
+        let input = "let f = fn(n) { if (n == 0) { 1 + true } else { 1 + g(n - 1) } };
+let g = fn(n) { 1 + f(n) };
+f(30)";
+        match test_eval_helper(input.to_string()).unwrap() {
+            Object::Error(err) => {
+                assert_eq!(err.frames.len(), 61);
+                let traceback = err.traceback();
+                let lines: Vec<&str> = traceback.lines().collect();
+                assert_eq!(lines.len(), 43);
+                assert_eq!(lines[1], "  f called at 3:1");
+                assert_eq!(lines[21], "  [21 more lines not shown]");
+                assert_eq!(lines[41], "  f called at 2:21");
+                assert_eq!(lines[42], "Error: type mismatch: INTEGER + BOOLEAN at 1:31");
+            }
+            obj => panic!("Object is not an error. Got: {}", obj),
+        }
     }
 }
@@ -1,3 +1,5 @@
+pub mod builtins;
+pub mod error;
 pub mod eval;
 pub mod evaluator_test;
 pub mod object;
@@ -1,72 +1,395 @@
+use super::builtins::Builtin;
+use super::error::{ErrorKind, RuntimeError};
+use crate::token::token::Span;
 use crate::AST::ast::FunctionLiteral;
-use std::cell::RefCell;
+use num_bigint::BigInt;
+use num_traits::ToPrimitive;
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 use std::fmt::Display;
-use std::rc::Rc;
+use std::rc::{Rc, Weak};
 
 pub type ObjectType = String;
 
 pub trait Obj {
     fn Type(&self) -> ObjectType;
 }
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq)]
 pub enum Object {
-    Integer(i32),
+    Integer(i64),
+    //only holds values outside the i64 range, see Object::from_big_int.
+    BigInteger(BigInt),
+    Float(f64),
+    String(String),
     Boolean(bool),
+    Array(Vec<Object>),
+    Hash(HashObject),
+    //lazy integer sequence from start up to, not including, end. step is never zero.
+    Range {
+        start: i64,
+        end: i64,
+        step: i64,
+    },
     Null,
     ReturnValue(Box<Object>),
-    Error(String),
+    //a call in tail position that hasn't been applied yet, see eval::apply_func.
+    TailCall {
+        func: Box<Object>,
+        args: Vec<Object>,
+        span: Span,
+    },
+    //loop control signals, propagated up to the enclosing while like ReturnValue.
+    Break,
+    Continue,
+    Error(RuntimeError),
     Function(FunctionLiteral, Enviornment),
+    Builtin(Builtin),
 }
 
-#[derive(PartialEq, Eq, Clone)]
-pub struct Enviornment {
+//the values usable as hash keys, floats and functions have no sensible equality to hash on.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub enum HashKey {
+    Integer(i64),
+    BigInteger(BigInt),
+    String(String),
+    Boolean(bool),
+}
+
+//insertion ordered, index maps a key to its position in pairs.
+#[derive(Clone, Default)]
+pub struct HashObject {
+    pairs: Vec<(Object, Object)>,
+    index: HashMap<HashKey, usize>,
+}
+
+impl HashObject {
+    pub fn new() -> HashObject {
+        HashObject::default()
+    }
+
+    pub fn get(&self, key: &HashKey) -> Option<&Object> {
+        self.index.get(key).map(|i| &self.pairs[*i].1)
+    }
+
+    //a key that is already present keeps its position and gets the new value.
+    pub fn insert(&mut self, hash_key: HashKey, key: Object, value: Object) {
+        match self.index.get(&hash_key) {
+            Some(i) => self.pairs[*i].1 = value,
+            None => {
+                self.index.insert(hash_key, self.pairs.len());
+                self.pairs.push((key, value));
+            }
+        }
+    }
+
+    pub fn pairs(&self) -> &Vec<(Object, Object)> {
+        &self.pairs
+    }
+}
+
+//equal when both hold the same pairs, regardless of insertion order.
+impl PartialEq for HashObject {
+    fn eq(&self, other: &Self) -> bool {
+        self.pairs.len() == other.pairs.len()
+            && self
+                .index
+                .iter()
+                .all(|(key, i)| other.get(key) == Some(&self.pairs[*i].1))
+    }
+}
+
+//a handle to a scope, clones share the scope instead of copying it, so an assignment in a
+//function or loop body updates the binding in the scope that owns it. A function keeps a
+//handle to the scope it was defined in, so it sees bindings added or changed after it was
+//created, itself included, which is what makes recursion and closures work. A function
+//stored in the scope it captured keeps that scope alive forever, such cycles are freed by
+//Enviornment::collect_cycles.
+#[derive(Clone)]
+pub struct Enviornment(Rc<RefCell<Scope>>);
+
+struct Scope {
     store: HashMap<String, Object>,
-    outer: Option<Rc<RefCell<Enviornment>>>,
+    outer: Option<Enviornment>,
+}
+
+//scopes created before the first collection. Later ones wait for twice the scopes that were
+//left alive and at least as many as the objects the last one looked at, so collecting takes
+//linear time overall.
+const MIN_SCOPES_TO_COLLECT: usize = 1024;
+
+thread_local! {
+    //every scope created on this thread that was alive at the last collection or made since.
+    static SCOPES: RefCell<Vec<Weak<RefCell<Scope>>>> = const { RefCell::new(Vec::new()) };
+    static SCOPES_TO_COLLECT: Cell<usize> = const { Cell::new(MIN_SCOPES_TO_COLLECT) };
+}
+
+impl Default for Enviornment {
+    fn default() -> Self {
+        Enviornment::new()
+    }
 }
 
 impl Enviornment {
     pub fn new() -> Enviornment {
-        Enviornment {
+        let env = Enviornment(Rc::new(RefCell::new(Scope {
             store: HashMap::new(),
             outer: None,
+        })));
+        let count = SCOPES.with(|scopes| {
+            let mut scopes = scopes.borrow_mut();
+            scopes.push(Rc::downgrade(&env.0));
+            scopes.len()
+        });
+        if count >= SCOPES_TO_COLLECT.with(|c| c.get()) {
+            Enviornment::collect_cycles();
+        }
+        return env;
+    }
+
+    //frees the scopes only kept alive by each other. Every reference to a scope comes from
+    //another scope, through its outer scope or a function in its store, or from outside of
+    //them, a handle held by the evaluator or the caller of eval. A scope with more references
+    //than the ones counted in other scopes is in use, as is any scope it reaches, the rest
+    //are garbage and are emptied, which drops the references keeping them alive.
+    pub fn collect_cycles() {
+        let scopes: Vec<Rc<RefCell<Scope>>> = SCOPES.with(|scopes| {
+            scopes
+                .borrow_mut()
+                .drain(..)
+                .filter_map(|s| s.upgrade())
+                .collect()
+        });
+        let index: HashMap<*const RefCell<Scope>, usize> = scopes
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (Rc::as_ptr(s), i))
+            .collect();
+
+        let mut edges = Vec::with_capacity(scopes.len());
+        let mut counted = vec![0; scopes.len()];
+        let mut visited = 0;
+        for scope in scopes.iter() {
+            //a scope borrowed right now is in the middle of being used, try again later.
+            let Ok(scope) = scope.try_borrow() else {
+                edges.clear();
+                break;
+            };
+            let mut refs = Vec::new();
+            if let Some(outer) = &scope.outer {
+                refs.push(Rc::as_ptr(&outer.0));
+            }
+            for obj in scope.store.values() {
+                visited += obj.captured_scopes(&mut refs);
+            }
+            let refs: Vec<usize> = refs.iter().filter_map(|r| index.get(r).copied()).collect();
+            for i in refs.iter() {
+                counted[*i] += 1;
+            }
+            edges.push(refs);
+        }
+
+        let mut live = vec![true; scopes.len()];
+        if edges.len() == scopes.len() {
+            //the upgrade above holds one reference to every scope.
+            let mut pending: Vec<usize> = (0..scopes.len())
+                .filter(|i| Rc::strong_count(&scopes[*i]) - 1 > counted[*i])
+                .collect();
+            live.fill(false);
+            while let Some(i) = pending.pop() {
+                if !live[i] {
+                    live[i] = true;
+                    pending.extend(edges[i].iter().copied());
+                }
+            }
+        }
+        for (scope, _) in scopes.iter().zip(live.iter()).filter(|(_, live)| !**live) {
+            //nothing else can drop a garbage scope while scopes holds it.
+            let mut scope = scope.borrow_mut();
+            scope.store.clear();
+            scope.outer = None;
         }
+
+        let live: Vec<Weak<RefCell<Scope>>> = scopes
+            .iter()
+            .zip(live.iter())
+            .filter(|(_, live)| **live)
+            .map(|(scope, _)| Rc::downgrade(scope))
+            .collect();
+        let next = (live.len() * 2).max(visited).max(MIN_SCOPES_TO_COLLECT);
+        SCOPES_TO_COLLECT.with(|c| c.set(next));
+        SCOPES.with(|s| s.borrow_mut().extend(live));
+    }
+
+    //number of scopes alive on this thread.
+    pub fn live_scopes() -> usize {
+        SCOPES.with(|scopes| {
+            scopes
+                .borrow()
+                .iter()
+                .filter(|s| s.strong_count() > 0)
+                .count()
+        })
     }
 
     pub fn get(&self, ident: &String) -> Object {
-        let obj = self.store.get(ident);
-        match obj {
+        let scope = self.0.borrow();
+        match scope.store.get(ident) {
             Some(val) => val.clone(),
-            None => match self.outer {
-                Some(ref out) => Rc::try_unwrap(out.clone())
-                    .unwrap_or(RefCell::new(Enviornment::new()))
-                    .borrow_mut()
-                    .get(ident),
-                None => Object::Error(format!("identifier not found: {}", ident)),
+            None => match scope.outer {
+                Some(ref out) => out.get(ident),
+                None => Object::error(
+                    ErrorKind::UndefinedIdentifier,
+                    format!("identifier not found: {}", ident),
+                ),
             },
         }
     }
 
-    pub fn set(&mut self, ident: String, obj: &Object) {
-        self.store.insert(ident, obj.clone());
+    pub fn set(&self, ident: String, obj: &Object) {
+        self.0.borrow_mut().store.insert(ident, obj.clone());
+    }
+
+    //updates the nearest scope that already binds ident, false when none does.
+    pub fn assign(&self, ident: &str, obj: &Object) -> bool {
+        let mut scope = self.0.borrow_mut();
+        if let Some(val) = scope.store.get_mut(ident) {
+            *val = obj.clone();
+            return true;
+        }
+        match scope.outer {
+            Some(ref out) => out.assign(ident, obj),
+            None => false,
+        }
     }
 
-    pub fn new_enclosed_env(outer: &mut Enviornment) -> Enviornment {
-        let mut env = Enviornment::new();
-        env.outer = Some(Rc::new(RefCell::new(outer.clone())));
+    pub fn new_enclosed_env(outer: &Enviornment) -> Enviornment {
+        let env = Enviornment::new();
+        env.0.borrow_mut().outer = Some(outer.clone());
         return env;
     }
 }
 
+//two handles are equal when they share a scope, comparing the contents could recurse
+//forever through a function stored in the scope it captured.
+impl PartialEq for Enviornment {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Object {
+    //numeric value as a float, ints are converted so mixed arithmetic can be done in floats.
+    pub fn to_float(&self) -> Option<f64> {
+        match self {
+            Object::Integer(i) => Some(*i as f64),
+            Object::BigInteger(num) => num.to_f64(),
+            Object::Float(num) => Some(*num),
+            _ => None,
+        }
+    }
+
+    pub fn to_big_int(&self) -> Option<BigInt> {
+        match self {
+            Object::Integer(i) => Some(BigInt::from(*i)),
+            Object::BigInteger(num) => Some(num.clone()),
+            _ => None,
+        }
+    }
+
+    //the elements a for loop visits, None when the object can't be iterated.
+    pub fn iter(&self) -> Option<Box<dyn Iterator<Item = Object> + '_>> {
+        match self {
+            Object::String(s) => Some(Box::new(s.chars().map(|c| Object::String(c.to_string())))),
+            Object::Array(elements) => Some(Box::new(elements.iter().cloned())),
+            Object::Hash(hash) => Some(Box::new(hash.pairs().iter().map(|(k, _)| k.clone()))),
+            Object::Range { start, end, step } => {
+                let (end, step) = (*end, *step);
+                let items = std::iter::successors(Some(*start), move |i| i.checked_add(step))
+                    .take_while(move |i| if step > 0 { *i < end } else { *i > end })
+                    .map(Object::Integer);
+                Some(Box::new(items))
+            }
+            _ => None,
+        }
+    }
+
+    //adds the scope of every function in the object, nested ones included, and returns the
+    //number of objects looked at.
+    fn captured_scopes(&self, found: &mut Vec<*const RefCell<Scope>>) -> usize {
+        let mut visited = 0;
+        let mut pending = vec![self];
+        while let Some(obj) = pending.pop() {
+            visited += 1;
+            match obj {
+                Object::Function(_, env) => found.push(Rc::as_ptr(&env.0)),
+                Object::Array(elements) => pending.extend(elements.iter()),
+                Object::Hash(hash) => {
+                    for (key, value) in hash.pairs() {
+                        pending.push(key);
+                        pending.push(value);
+                    }
+                }
+                Object::ReturnValue(val) => pending.push(val),
+                Object::TailCall { func, args, .. } => {
+                    pending.push(func);
+                    pending.extend(args.iter());
+                }
+                _ => {}
+            }
+        }
+        return visited;
+    }
+
+    pub fn hash_key(&self) -> Option<HashKey> {
+        match self {
+            Object::Integer(i) => Some(HashKey::Integer(*i)),
+            Object::BigInteger(num) => Some(HashKey::BigInteger(num.clone())),
+            Object::String(s) => Some(HashKey::String(s.clone())),
+            Object::Boolean(b) => Some(HashKey::Boolean(*b)),
+            _ => None,
+        }
+    }
+
+    //how the object prints inside a collection, strings are quoted so ["1"] and [1] differ.
+    fn inspect(&self) -> String {
+        match self {
+            Object::String(s) => format!("{:?}", s),
+            _ => self.to_string(),
+        }
+    }
+
+    //demotes to Integer when the value fits in an i64, so every integer has exactly one
+    //representation and equality or hashing never depend on how the value was computed.
+    pub fn from_big_int(num: BigInt) -> Object {
+        match num.to_i64() {
+            Some(i) => Object::Integer(i),
+            None => Object::BigInteger(num),
+        }
+    }
+
+    pub fn error(kind: ErrorKind, message: String) -> Object {
+        Object::Error(RuntimeError::new(kind, message))
+    }
+}
+
 impl Obj for Object {
     fn Type(&self) -> ObjectType {
         match *self {
-            Object::Integer(_) => "INTEGER".to_string(),
+            Object::Integer(_) | Object::BigInteger(_) => "INTEGER".to_string(),
+            Object::Float(_) => "FLOAT".to_string(),
+            Object::String(_) => "STRING".to_string(),
             Object::Boolean(_) => "BOOLEAN".to_string(),
+            Object::Array(_) => "ARRAY".to_string(),
+            Object::Hash(_) => "HASH".to_string(),
+            Object::Range { .. } => "RANGE".to_string(),
             Object::Null => "NULL".to_string(),
             Object::ReturnValue(_) => "RETURN_VALUE".to_string(),
+            Object::TailCall { .. } => "TAIL_CALL".to_string(),
+            Object::Break => "BREAK".to_string(),
+            Object::Continue => "CONTINUE".to_string(),
             Object::Error(_) => "ERROR".to_string(),
             Object::Function(_, _) => "FUNCTION".to_string(),
+            Object::Builtin(_) => "BUILTIN".to_string(),
         }
     }
 }
@@ -75,22 +398,45 @@ impl Display for Object {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Object::Integer(i) => write!(f, "{}", i),
+            Object::BigInteger(num) => write!(f, "{}", num),
+            //debug formatting keeps the fraction, so 2.0 isn't printed as an integer.
+            Object::Float(num) => write!(f, "{:?}", num),
+            Object::String(s) => write!(f, "{}", s),
             Object::Boolean(b) => write!(f, "{}", b),
-            Object::Null => write!(f, "{}", "null"),
+            Object::Array(elements) => {
+                let mut items = Vec::new();
+                for e in elements {
+                    items.push(e.inspect());
+                }
+                write!(f, "[{}]", items.join(", "))
+            }
+            Object::Hash(hash) => {
+                let mut pairs = Vec::new();
+                for (key, value) in hash.pairs() {
+                    pairs.push(format!("{}: {}", key.inspect(), value.inspect()));
+                }
+                write!(f, "{{{}}}", pairs.join(", "))
+            }
+            Object::Range { start, end, step } => {
+                if *step == 1 {
+                    return write!(f, "range({}, {})", start, end);
+                }
+                write!(f, "range({}, {}, {})", start, end, step)
+            }
+            Object::Null => write!(f, "null"),
             Object::ReturnValue(val) => write!(f, "{}", val),
+            Object::TailCall { func, .. } => write!(f, "tail call to {}", func),
+            Object::Break => write!(f, "break"),
+            Object::Continue => write!(f, "continue"),
             Object::Error(err) => write!(f, "Error: {}", err),
             Object::Function(func, _) => {
                 let mut params = Vec::new();
                 for param in &func.params {
                     params.push(param.to_string());
                 }
-                write!(
-                    f,
-                    "fn({}){{\n{}\n}}",
-                    params.join(", "),
-                    func.body.to_string()
-                )
+                write!(f, "fn({}){{\n{}\n}}", params.join(", "), func.body)
             }
+            Object::Builtin(builtin) => write!(f, "builtin function {}", builtin.name),
         }
     }
 }
@@ -1,215 +1,486 @@
 use crate::token::token::*;
+use num_bigint::BigInt;
+use unicode_xid::UnicodeXID;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum CommentKind {
+    Line,
+    Block,
+}
+
+//comments are skipped in the token stream, but kept (with their delimiters) for tools such as
+//formatters that need to put them back.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Comment {
+    pub kind: CommentKind,
+    pub text: String,
+    pub span: Span,
+}
+
+//a malformed token or comment, the lexer reports it and carries on with an Illegal token.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LexError {
+    pub message: String,
+    pub span: Span,
+}
+
+impl std::fmt::Display for LexError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at {}", self.message, self.span)
+    }
+}
 
 #[derive(Clone)]
 pub struct Lexer {
     pub input: String,
     pub ch: char,
+    //byte offset of `ch` in the input.
+    position: usize,
+    //byte offset of the character after `ch`.
+    read_position: usize,
+    //1-based line and column (in chars) of `ch`.
+    line: usize,
+    col: usize,
+    //where the token currently being scanned starts.
+    tok_start: Span,
+    pub errors: Vec<LexError>,
+    pub comments: Vec<Comment>,
 }
-fn consume_first_word(input: &str) -> String {
-    if let Some(index) = input.find(' ') {
-        let remaining = &input[index + 1..];
-        return String::from(remaining);
-    }
-    String::new()
+
+//identifiers follow Unicode's XID_Start/XID_Continue rules, and may also start with _.
+fn is_ident_start(c: char) -> bool {
+    c == '_' || c.is_xid_start()
 }
-fn consume_white_space(input: &str) -> String {
-    let mut idx = 0;
-    for c in input.chars() {
-        if c != '\n' && c != ' ' && c != '\t' && c != '\r' {
-            let remaining = &input[idx..];
-            return String::from(remaining);
-        }
-        idx += 1;
+
+fn is_ident_continue(c: char) -> bool {
+    c.is_xid_continue()
+}
+
+fn radix_name(radix: u32) -> &'static str {
+    match radix {
+        2 => "binary",
+        8 => "octal",
+        16 => "hexadecimal",
+        _ => "decimal",
     }
-    String::new()
 }
 
-fn insert_str_at_start(append_to: &str, to_append: &str) -> String {
-    let mut temp: String = String::from(to_append);
-    temp = temp + append_to;
-    temp
+//checks a run of digits in the given radix, underscores are only allowed between digits.
+fn check_digits(digits: &str, radix: u32) -> Result<(), String> {
+    if digits.is_empty() {
+        return Err(format!("missing digits in {} literal", radix_name(radix)));
+    }
+    if let Some(c) = digits.chars().find(|c| *c != '_' && !c.is_digit(radix)) {
+        return Err(format!(
+            "invalid digit '{}' in {} literal",
+            c,
+            radix_name(radix)
+        ));
+    }
+    if digits.starts_with('_') || digits.ends_with('_') || digits.contains("__") {
+        return Err(format!(
+            "misplaced underscore in {} literal",
+            radix_name(radix)
+        ));
+    }
+    Ok(())
 }
 
-//to use only on "Constructor", could be used on nextToken but not preferable.
-#[allow(unused_assignments)]
-fn split_special_chars(inp: &str) -> String {
-    let mut s = String::new();
-    s = inp.replace('(', " ( ");
-    s = s.replace(')', " ) ");
-    s = s.replace('{', " { ");
-    s = s.replace('}', " } ");
-    s = s.replace(',', " , ");
-    s = s.replace(';', " ; ");
-    s = s.replace('-', " - ");
-    s = s.replace('*', " * ");
-    s = s.replace('/', " / ");
-    s = s.replace('<', " < ");
-    s = s.replace('>', " > ");
-    s = s.replace('!', " ! ");
-    s = s.replace('+', " + ");
-    return s;
+//checks a decimal literal of the form int[.frac][e[+-]exp], returns whether it is a float.
+fn check_decimal(literal: &str) -> Result<bool, String> {
+    let (mantissa, exponent) = match literal.find(['e', 'E']) {
+        Some(idx) => (&literal[..idx], Some(&literal[idx + 1..])),
+        None => (literal, None),
+    };
+    let (int_part, frac_part) = match mantissa.split_once('.') {
+        Some((int_part, frac_part)) => (int_part, Some(frac_part)),
+        None => (mantissa, None),
+    };
+    //the integer part may only be left out in floats such as .5
+    if !int_part.is_empty() || frac_part.is_none() {
+        check_digits(int_part, 10)?;
+    }
+    if let Some(frac_part) = frac_part {
+        check_digits(frac_part, 10)?;
+    }
+    if let Some(exponent) = exponent {
+        check_digits(exponent.trim_start_matches(['+', '-']), 10)?;
+    }
+    Ok(frac_part.is_some() || exponent.is_some())
 }
 
-fn is_token_number(tok: &str) -> bool {
-    for c in tok.chars() {
-        if !c.is_numeric() {
-            return false;
-        }
+fn new_token(tok_type: TokenType, literal: &str) -> Token {
+    Token {
+        tok_type,
+        literal: literal.to_string(),
+        span: Span::default(),
     }
-    true
 }
 
 impl Lexer {
-    pub fn new(&mut self, input: String) -> Self {
-        Self {
-            input: input.clone(),
-            ch: input.chars().next().unwrap(),
+    pub fn new(input: &str) -> Lexer {
+        let mut lex = Lexer {
+            input: input.to_string(),
+            ch: '\0',
+            position: 0,
+            read_position: 0,
+            line: 1,
+            col: 0,
+            tok_start: Span::default(),
+            errors: vec![],
+            comments: vec![],
         };
-        self.input = split_special_chars(&mut self.input);
-        return self.clone();
+        lex.read_char();
+        //some editors start files with a byte order mark, skip it without counting a column.
+        if lex.ch == '\u{feff}' {
+            lex.read_char();
+            lex.col = 1;
+        }
+        //a #! line at the very start is a shebang for running scripts directly, keep it as a comment.
+        if lex.input[lex.position..].starts_with("#!") {
+            let start = lex.current_span();
+            lex.skip_line_comment();
+            lex.push_comment(CommentKind::Line, start);
+        }
+        lex
+    }
+
+    fn at_end(&self) -> bool {
+        self.position >= self.input.len()
     }
-    pub fn next_token(&mut self) -> Token {
-        let binding = self.input.clone();
-        let first_token = binding.split_whitespace().next().unwrap_or("");
-        let mut itr = first_token.chars();
 
-        self.input = consume_white_space(&self.input);
-        self.input = consume_first_word(&self.input);
+    fn read_char(&mut self) {
+        if self.ch == '\n' {
+            self.line += 1;
+            self.col = 1;
+        } else {
+            self.col += 1;
+        }
+        self.position = self.read_position;
+        match self.input[self.read_position..].chars().next() {
+            Some(c) => {
+                self.ch = c;
+                self.read_position += c.len_utf8();
+            }
+            None => self.ch = '\0',
+        }
+    }
 
-        self.ch = itr.next().unwrap_or('~');
-        let peek_char = itr.next().unwrap_or('~');
+    fn peek_char(&self) -> char {
+        self.input[self.read_position..]
+            .chars()
+            .next()
+            .unwrap_or('\0')
+    }
 
-        match first_token {
-            "," => Token {
-                tok_type: TokenType::Comma,
-                literal: ",".to_string(),
-            },
-            ";" => Token {
-                tok_type: TokenType::Semicolon,
-                literal: ";".to_string(),
-            },
-            "(" => Token {
-                tok_type: TokenType::Lparen,
-                literal: "(".to_string(),
-            },
-            ")" => Token {
-                tok_type: TokenType::Rparen,
-                literal: ")".to_string(),
-            },
-            "{" => Token {
-                tok_type: TokenType::Lbrack,
-                literal: "{".to_string(),
-            },
-            "}" => Token {
-                tok_type: TokenType::Rbrack,
-                literal: "}".to_string(),
-            },
-            "+" => Token {
-                tok_type: TokenType::Plus,
-                literal: "+".to_string(),
-            },
-            "*" => Token {
-                tok_type: TokenType::Asterisk,
-                literal: "*".to_string(),
-            },
-            "/" => Token {
-                tok_type: TokenType::Fslash,
-                literal: "/".to_string(),
-            },
-            "-" => Token {
-                tok_type: TokenType::Minus,
-                literal: "-".to_string(),
-            },
-            ">" => Token {
-                tok_type: TokenType::Rarrow,
-                literal: ">".to_string(),
-            },
-            "<" => Token {
-                tok_type: TokenType::Larrow,
-                literal: "<".to_string(),
+    fn skip_whitespace(&mut self) {
+        while !self.at_end() && self.ch.is_whitespace() {
+            self.read_char();
+        }
+    }
+
+    fn current_span(&self) -> Span {
+        Span {
+            start: self.position,
+            end: self.position,
+            line: self.line,
+            col: self.col,
+        }
+    }
+
+    //skips whitespace and comments, recording the comments.
+    fn skip_trivia(&mut self) {
+        self.skip_whitespace();
+        while self.ch == '/' && (self.peek_char() == '/' || self.peek_char() == '*') {
+            let start = self.current_span();
+            let kind = if self.peek_char() == '/' {
+                self.skip_line_comment();
+                CommentKind::Line
+            } else {
+                self.skip_block_comment(start);
+                CommentKind::Block
+            };
+            self.push_comment(kind, start);
+            self.skip_whitespace();
+        }
+    }
+
+    //records the comment from start up to the current position.
+    fn push_comment(&mut self, kind: CommentKind, start: Span) {
+        self.comments.push(Comment {
+            kind,
+            text: self.input[start.start..self.position].to_string(),
+            span: Span {
+                end: self.position,
+                ..start
             },
+        });
+    }
 
-            _ => {
-                //Check for != or !IDENT
-                if self.ch == '!' {
-                    let mut eq = binding.chars();
-                    eq.next();
-                    eq.next();
-                    eq.next();
-
-                    if eq.next().unwrap_or('~') == '=' {
-                        self.input = consume_white_space(&self.input);
-                        self.input = consume_first_word(&self.input);
-                        return Token {
-                            tok_type: TokenType::NotEQ,
-                            literal: "!=".to_string(),
-                        };
-                    }
+    fn skip_line_comment(&mut self) {
+        while !self.at_end() && self.ch != '\n' {
+            self.read_char();
+        }
+    }
 
-                    let mut temp: String = String::from(first_token);
-                    temp.remove(0);
-                    self.input = insert_str_at_start(&self.input, &temp);
-                    return Token {
-                        tok_type: TokenType::Not,
-                        literal: "!".to_string(),
-                    };
+    //block comments nest, so /* a /* b */ c */ is a single comment.
+    fn skip_block_comment(&mut self, start: Span) {
+        let mut depth = 0;
+        loop {
+            if self.at_end() {
+                self.errors.push(LexError {
+                    message: "unterminated block comment".to_string(),
+                    span: start,
+                });
+                return;
+            }
+            if self.ch == '/' && self.peek_char() == '*' {
+                depth += 1;
+                self.read_char();
+            } else if self.ch == '*' && self.peek_char() == '/' {
+                depth -= 1;
+                self.read_char();
+                if depth == 0 {
+                    self.read_char();
+                    return;
                 }
+            }
+            self.read_char();
+        }
+    }
 
-                //Check for assignment or equality
-                if self.ch == '=' {
-                    if peek_char == '=' {
-                        let mut temp: String = String::from(first_token);
-                        temp.remove(0);
-                        temp.remove(0);
-                        self.input = insert_str_at_start(&self.input, &temp);
-                        return Token {
-                            tok_type: TokenType::EQ,
-                            literal: "==".to_string(),
-                        };
-                    }
-                    let mut temp: String = String::from(first_token);
-                    temp.remove(0);
-                    self.input = insert_str_at_start(&self.input, &temp);
-
-                    return Token {
-                        tok_type: TokenType::Assign,
-                        literal: "=".to_string(),
-                    };
-                }
+    fn error(&mut self, msg: &str) {
+        self.errors.push(LexError {
+            message: msg.to_string(),
+            span: self.tok_start,
+        });
+    }
 
-                //TODO if a char is alphabetic and contains = or != than seprate them and and add
-                //back the == or != or = to the input.
-                if first_token.contains("==") {}
+    //consumes the current char and the peeked one, used for two char operators such as ==.
+    fn two_char_token(&mut self, tok_type: TokenType, literal: &str) -> Token {
+        self.read_char();
+        new_token(tok_type, literal)
+    }
 
-                if first_token.contains("!=") {}
+    fn read_identifier(&mut self) -> Token {
+        let start = self.position;
+        while !self.at_end() && is_ident_continue(self.ch) {
+            self.read_char();
+        }
+        let ident = &self.input[start..self.position];
+        new_token(lookup_ident(ident), ident)
+    }
 
-                if first_token.contains("=") {}
+    //consumes identifier characters, used to take malformed literals such as 12ab
+    //as a whole so they can be reported as one error.
+    fn read_word(&mut self) {
+        while !self.at_end() && is_ident_continue(self.ch) {
+            self.read_char();
+        }
+    }
 
-                if self.ch.is_ascii_alphabetic() {
-                    Token {
-                        literal: first_token.to_string(),
-                        tok_type: lookup_ident(first_token),
-                    }
-                } else if first_token == "" {
-                    Token {
-                        literal: first_token.to_string(),
-                        tok_type: TokenType::EOF,
-                    }
-                } else if is_token_number(first_token) {
-                    let num: i32 = first_token.parse().unwrap();
-                    Token {
-                        //when parsing, note for other chars such as ;. there is a need to check
-                        //each character in the token is actually an int.
-                        literal: num.to_string(),
-                        tok_type: TokenType::Int(num),
-                    }
-                } else {
-                    Token {
-                        tok_type: TokenType::Illegal,
-                        literal: "".to_string(),
+    //reads an integer or a float such as 3.14, .5 or 1e-9. Integers may also be written in
+    //hex (0xFF), binary (0b1010) or octal (0o755), and digits may be grouped with underscores.
+    fn read_number(&mut self) -> Token {
+        let start = self.position;
+        let radix = match (self.ch, self.peek_char()) {
+            ('0', 'x' | 'X') => 16,
+            ('0', 'b' | 'B') => 2,
+            ('0', 'o' | 'O') => 8,
+            _ => 10,
+        };
+        if radix != 10 {
+            self.read_char();
+            self.read_char();
+        }
+        let digits_start = self.position;
+        self.read_word();
+        if radix == 10 {
+            let has_exponent = self.input[start..self.position].contains(['e', 'E']);
+            if self.ch == '.' && self.peek_char().is_ascii_digit() && !has_exponent {
+                self.read_char();
+                self.read_word();
+            }
+            if self.input[start..self.position].ends_with(['e', 'E'])
+                && (self.ch == '+' || self.ch == '-')
+                && self.peek_char().is_ascii_digit()
+            {
+                self.read_char();
+                self.read_word();
+            }
+        }
+
+        let literal = self.input[start..self.position].to_string();
+        let digits = &self.input[digits_start..self.position];
+        let checked = if radix == 10 {
+            check_decimal(digits)
+        } else {
+            check_digits(digits, radix).map(|_| false)
+        };
+        let digits: String = digits.chars().filter(|c| *c != '_').collect();
+
+        match checked {
+            Err(msg) => {
+                self.error(&msg);
+                new_token(TokenType::Illegal, &literal)
+            }
+            Ok(true) => match digits.parse::<f64>() {
+                Ok(num) if num.is_finite() => new_token(TokenType::Float(num), &literal),
+                _ => {
+                    self.error(&format!("float literal {} is out of range", literal));
+                    new_token(TokenType::Illegal, &literal)
+                }
+            },
+            Ok(false) => match i64::from_str_radix(&digits, radix) {
+                Ok(num) => new_token(TokenType::Int(num), &literal),
+                //only fails on overflow since the digits were checked.
+                Err(_) => match BigInt::parse_bytes(digits.as_bytes(), radix) {
+                    Some(num) => new_token(TokenType::BigInt, &num.to_string()),
+                    None => {
+                        self.error(&format!("invalid integer literal {}", literal));
+                        new_token(TokenType::Illegal, &literal)
                     }
+                },
+            },
+        }
+    }
+
+    //reads a quoted string, leaving `ch` on the closing quote. The literal is the unescaped value.
+    fn read_string(&mut self, quote: char) -> Token {
+        let start = self.position;
+        let mut value = String::new();
+        let mut valid = true;
+        self.read_char();
+        while self.ch != quote {
+            if self.at_end() {
+                self.error("unterminated string literal");
+                return new_token(TokenType::Illegal, &self.input[start..]);
+            }
+            if self.ch == '\\' {
+                self.read_char();
+                match self.read_escape() {
+                    Some(c) => value.push(c),
+                    None => valid = false,
                 }
+            } else {
+                value.push(self.ch);
+            }
+            self.read_char();
+        }
+        if !valid {
+            return new_token(TokenType::Illegal, &self.input[start..self.read_position]);
+        }
+        new_token(TokenType::Str, &value)
+    }
+
+    //reads the escape sequence following a backslash, leaving `ch` on its last char.
+    fn read_escape(&mut self) -> Option<char> {
+        match self.ch {
+            'n' => Some('\n'),
+            't' => Some('\t'),
+            'r' => Some('\r'),
+            '0' => Some('\0'),
+            '\\' => Some('\\'),
+            '\'' => Some('\''),
+            '"' => Some('"'),
+            'u' => self.read_unicode_escape(),
+            _ if self.at_end() => None,
+            c => {
+                self.error(&format!("unknown escape sequence \\{}", c));
+                None
             }
         }
     }
+
+    //reads the `{XXXX}` part of a \u{XXXX} escape.
+    fn read_unicode_escape(&mut self) -> Option<char> {
+        if self.peek_char() != '{' {
+            self.error("expected '{' after \\u");
+            return None;
+        }
+        self.read_char();
+        let start = self.read_position;
+        while self.peek_char().is_ascii_hexdigit() {
+            self.read_char();
+        }
+        let digits = self.input[start..self.read_position].to_string();
+        if self.peek_char() != '}' {
+            self.error("unterminated unicode escape");
+            return None;
+        }
+        self.read_char();
+        let c = u32::from_str_radix(&digits, 16)
+            .ok()
+            .and_then(char::from_u32);
+        if c.is_none() {
+            self.error(&format!("invalid unicode escape \\u{{{}}}", digits));
+        }
+        c
+    }
+
+    pub fn next_token(&mut self) -> Token {
+        self.skip_trivia();
+        self.tok_start = self.current_span();
+        let mut tok = self.scan_token();
+        tok.span = Span {
+            end: self.position,
+            ..self.tok_start
+        };
+        tok
+    }
+
+    fn scan_token(&mut self) -> Token {
+        if self.at_end() {
+            return new_token(TokenType::EOF, "");
+        }
+
+        let tok = match self.ch {
+            '=' if self.peek_char() == '=' => self.two_char_token(TokenType::EQ, "=="),
+            '=' => new_token(TokenType::Assign, "="),
+            '!' if self.peek_char() == '=' => self.two_char_token(TokenType::NotEQ, "!="),
+            '!' => new_token(TokenType::Not, "!"),
+            ',' => new_token(TokenType::Comma, ","),
+            ':' => new_token(TokenType::Colon, ":"),
+            ';' => new_token(TokenType::Semicolon, ";"),
+            '(' => new_token(TokenType::Lparen, "("),
+            ')' => new_token(TokenType::Rparen, ")"),
+            '{' => new_token(TokenType::Lbrack, "{"),
+            '}' => new_token(TokenType::Rbrack, "}"),
+            '[' => new_token(TokenType::Lsquare, "["),
+            ']' => new_token(TokenType::Rsquare, "]"),
+            '+' if self.peek_char() == '=' => self.two_char_token(TokenType::PlusAssign, "+="),
+            '+' => new_token(TokenType::Plus, "+"),
+            '-' if self.peek_char() == '=' => self.two_char_token(TokenType::MinusAssign, "-="),
+            '-' => new_token(TokenType::Minus, "-"),
+            '*' if self.peek_char() == '*' => self.two_char_token(TokenType::Power, "**"),
+            '*' if self.peek_char() == '=' => self.two_char_token(TokenType::AsteriskAssign, "*="),
+            '*' => new_token(TokenType::Asterisk, "*"),
+            '%' if self.peek_char() == '=' => self.two_char_token(TokenType::PercentAssign, "%="),
+            '%' => new_token(TokenType::Percent, "%"),
+            '/' if self.peek_char() == '=' => self.two_char_token(TokenType::FslashAssign, "/="),
+            '/' => new_token(TokenType::Fslash, "/"),
+            '<' if self.peek_char() == '=' => self.two_char_token(TokenType::LarrowEQ, "<="),
+            '<' if self.peek_char() == '<' => self.two_char_token(TokenType::LShift, "<<"),
+            '<' => new_token(TokenType::Larrow, "<"),
+            '>' if self.peek_char() == '=' => self.two_char_token(TokenType::RarrowEQ, ">="),
+            '>' if self.peek_char() == '>' => self.two_char_token(TokenType::RShift, ">>"),
+            '>' => new_token(TokenType::Rarrow, ">"),
+            '&' if self.peek_char() == '&' => self.two_char_token(TokenType::And, "&&"),
+            '&' => new_token(TokenType::Ampersand, "&"),
+            '|' if self.peek_char() == '|' => self.two_char_token(TokenType::Or, "||"),
+            '|' => new_token(TokenType::Pipe, "|"),
+            '^' => new_token(TokenType::Caret, "^"),
+            '~' => new_token(TokenType::Tilde, "~"),
+            c if is_ident_start(c) => return self.read_identifier(),
+            c if c.is_ascii_digit() => return self.read_number(),
+            '.' if self.peek_char().is_ascii_digit() => return self.read_number(),
+            c @ ('"' | '\'') => self.read_string(c),
+            c => {
+                self.error(&format!("illegal character '{}'", c));
+                new_token(TokenType::Illegal, &c.to_string())
+            }
+        };
+        self.read_char();
+        tok
+    }
 }
@@ -18,16 +18,12 @@ mod tests {
             (TokenType::EOF, ""),
         ];
 
-        let mut x = Lexer {
-            input: input.to_string(),
-            ch: '=',
-        };
-        let mut lex = Lexer::new(&mut x, input.to_string());
+        let mut lex = Lexer::new(input);
 
-        for (_, testTup) in vec.iter().enumerate() {
+        for test_tup in vec.iter() {
             let tok = lex.next_token();
-            assert_eq!(tok.tok_type, testTup.0);
-            assert_eq!(tok.literal, testTup.1);
+            assert_eq!(tok.tok_type, test_tup.0);
+            assert_eq!(tok.literal, test_tup.1);
         }
     }
     #[test]
@@ -78,13 +74,9 @@ mod tests {
             (TokenType::EOF, ""),
         ];
 
-        let mut x = Lexer {
-            input: input.to_string(),
-            ch: 'l',
-        };
-        let mut lex = Lexer::new(&mut x, input.to_string());
+        let mut lex = Lexer::new(input);
 
-        for (_, test_tup) in vec.iter().enumerate() {
+        for test_tup in vec.iter() {
             let tok = lex.next_token();
             assert_eq!(tok.tok_type, test_tup.0);
             assert_eq!(tok.literal, test_tup.1);
@@ -98,7 +90,7 @@ mod tests {
                     x + y;
                     };
                     let result = add(five,ten);
-                    !-/*5;
+                    !-/ *5;
                     5 < 10 > 5;";
         let vec: Vec<(TokenType, &str)> = vec![
             (TokenType::Let, "let"),
@@ -151,13 +143,9 @@ mod tests {
             (TokenType::Semicolon, ";"),
         ];
 
-        let mut x = Lexer {
-            input: input.to_string(),
-            ch: 'l',
-        };
-        let mut lex = Lexer::new(&mut x, input.to_string());
+        let mut lex = Lexer::new(input);
 
-        for (_, test_tup) in vec.iter().enumerate() {
+        for test_tup in vec.iter() {
             let tok = lex.next_token();
             assert_eq!(tok.tok_type, test_tup.0);
             assert_eq!(tok.literal, test_tup.1);
@@ -171,7 +159,7 @@ mod tests {
                         x + y;
                         };
                         let result = add(five,ten);
-                        !-/*5;
+                        !-/ *5;
                         5 < 10 > 5;
 
                         if 5 < 10 {
@@ -256,16 +244,540 @@ mod tests {
             (TokenType::Semicolon, ";"),
         ];
 
-        let mut x = Lexer {
-            input: input.to_string(),
-            ch: 'l',
-        };
-        let mut lex = Lexer::new(&mut x, input.to_string());
+        let mut lex = Lexer::new(input);
+
+        for test_tup in vec.iter() {
+            let tok = lex.next_token();
+            assert_eq!(tok.tok_type, test_tup.0);
+            assert_eq!(tok.literal, test_tup.1);
+        }
+    }
+
+    #[test]
+    fn test_next_token_spacing_insensitive() {
+        let tests = vec![
+            ("x==y", "x == y"),
+            ("a!=b", "a != b"),
+            ("let x=5;", "let x = 5 ;"),
+            ("!x", "! x"),
+            ("!true==false", "! true == false"),
+            ("add(x,y)*-2", "add ( x , y ) * - 2"),
+            (
+                "if(a<b){return a;}else{return b;}",
+                "if ( a < b ) { return a ; } else { return b ; }",
+            ),
+        ];
+
+        for (glued, spaced) in &tests {
+            let mut glued_lex = Lexer::new(glued);
+            let mut spaced_lex = Lexer::new(spaced);
+            loop {
+                let glued_tok = glued_lex.next_token();
+                let spaced_tok = spaced_lex.next_token();
+                assert_eq!(glued_tok.tok_type, spaced_tok.tok_type, "input: {}", glued);
+                assert_eq!(glued_tok.literal, spaced_tok.literal, "input: {}", glued);
+                if glued_tok.tok_type == TokenType::EOF {
+                    break;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_next_token_illegal_and_eof() {
+        let mut lex = Lexer::new("");
+        assert_eq!(lex.next_token().tok_type, TokenType::EOF);
+        assert_eq!(lex.next_token().tok_type, TokenType::EOF);
+
+        let mut lex = Lexer::new("x@y");
+        assert_eq!(lex.next_token().tok_type, TokenType::Ident);
+        let tok = lex.next_token();
+        assert_eq!(tok.tok_type, TokenType::Illegal);
+        assert_eq!(tok.literal, "@");
+        assert_eq!(lex.next_token().tok_type, TokenType::Ident);
+        assert_eq!(lex.next_token().tok_type, TokenType::EOF);
+    }
+
+    #[test]
+    fn test_next_token_spans() {
+        let input = "let x=5;\n  x == 10;";
+        let expected = vec![
+            (TokenType::Let, 0, 3, 1, 1),
+            (TokenType::Ident, 4, 5, 1, 5),
+            (TokenType::Assign, 5, 6, 1, 6),
+            (TokenType::Int(5), 6, 7, 1, 7),
+            (TokenType::Semicolon, 7, 8, 1, 8),
+            (TokenType::Ident, 11, 12, 2, 3),
+            (TokenType::EQ, 13, 15, 2, 5),
+            (TokenType::Int(10), 16, 18, 2, 8),
+            (TokenType::Semicolon, 18, 19, 2, 10),
+            (TokenType::EOF, 19, 19, 2, 11),
+        ];
+
+        let mut lex = Lexer::new(input);
+        for (tok_type, start, end, line, col) in expected {
+            let tok = lex.next_token();
+            assert_eq!(tok.tok_type, tok_type);
+            assert_eq!(
+                (tok.span.start, tok.span.end, tok.span.line, tok.span.col),
+                (start, end, line, col),
+                "unexpected span for {}",
+                tok
+            );
+            assert_eq!(&input[tok.span.start..tok.span.end], tok.literal);
+        }
+    }
+
+    #[test]
+    fn test_next_token_strings() {
+        let input = r#"'Hello' + "World"; "it's" 'say "hi"' "a\tb\n" '\\ \' \u{48}\u{1F600}' """#;
+        let vec: Vec<(TokenType, &str)> = vec![
+            (TokenType::Str, "Hello"),
+            (TokenType::Plus, "+"),
+            (TokenType::Str, "World"),
+            (TokenType::Semicolon, ";"),
+            (TokenType::Str, "it's"),
+            (TokenType::Str, "say \"hi\""),
+            (TokenType::Str, "a\tb\n"),
+            (TokenType::Str, "\\ ' H\u{1F600}"),
+            (TokenType::Str, ""),
+            (TokenType::EOF, ""),
+        ];
+
+        let mut lex = Lexer::new(input);
+        for test_tup in vec.iter() {
+            let tok = lex.next_token();
+            assert_eq!(tok.tok_type, test_tup.0);
+            assert_eq!(tok.literal, test_tup.1);
+        }
+        assert!(lex.errors.is_empty());
+    }
+
+    #[test]
+    fn test_next_token_string_errors() {
+        let tests = vec![
+            ("'abc", "unterminated string literal at 1:1"),
+            ("x = \"abc\\", "unterminated string literal at 1:5"),
+            ("'a\\qb'", "unknown escape sequence \\q at 1:1"),
+            ("'\\u{110000}'", "invalid unicode escape \\u{110000} at 1:1"),
+            ("'\\u{41'", "unterminated unicode escape at 1:1"),
+            ("'\\u41'", "expected '{' after \\u at 1:1"),
+        ];
+
+        for (input, expected) in &tests {
+            let mut lex = Lexer::new(input);
+            let mut tok = lex.next_token();
+            while tok.tok_type != TokenType::EOF && tok.tok_type != TokenType::Illegal {
+                tok = lex.next_token();
+            }
+            assert_eq!(tok.tok_type, TokenType::Illegal, "input: {}", input);
+            assert_eq!(
+                error_messages(&lex),
+                vec![expected.to_string()],
+                "input: {}",
+                input
+            );
+            while tok.tok_type != TokenType::EOF {
+                tok = lex.next_token();
+            }
+        }
+    }
+
+    #[test]
+    fn test_next_token_comments() {
+        let input = "// leading comment
+let x = 5; // trailing comment
+/* block /* nested */ still comment */ x /**/ + 1;
+// comment at eof";
+        let vec: Vec<(TokenType, &str)> = vec![
+            (TokenType::Let, "let"),
+            (TokenType::Ident, "x"),
+            (TokenType::Assign, "="),
+            (TokenType::Int(5), "5"),
+            (TokenType::Semicolon, ";"),
+            (TokenType::Ident, "x"),
+            (TokenType::Plus, "+"),
+            (TokenType::Int(1), "1"),
+            (TokenType::Semicolon, ";"),
+            (TokenType::EOF, ""),
+        ];
+
+        let mut lex = Lexer::new(input);
+        for test_tup in vec.iter() {
+            let tok = lex.next_token();
+            assert_eq!(tok.tok_type, test_tup.0);
+            assert_eq!(tok.literal, test_tup.1);
+        }
+        assert!(lex.errors.is_empty());
+
+        let comments: Vec<(&CommentKind, &str, usize, usize)> = lex
+            .comments
+            .iter()
+            .map(|c| (&c.kind, c.text.as_str(), c.span.line, c.span.col))
+            .collect();
+        assert_eq!(
+            comments,
+            vec![
+                (&CommentKind::Line, "// leading comment", 1, 1),
+                (&CommentKind::Line, "// trailing comment", 2, 12),
+                (
+                    &CommentKind::Block,
+                    "/* block /* nested */ still comment */",
+                    3,
+                    1
+                ),
+                (&CommentKind::Block, "/**/", 3, 42),
+                (&CommentKind::Line, "// comment at eof", 4, 1),
+            ]
+        );
+        for c in &lex.comments {
+            assert_eq!(&input[c.span.start..c.span.end], c.text);
+        }
+    }
+
+    #[test]
+    fn test_next_token_shebang() {
+        let mut lex = Lexer::new("#!/usr/bin/env quokka\nlet x = 5;");
+        let tok = lex.next_token();
+        assert_eq!(tok.tok_type, TokenType::Let);
+        assert_eq!((tok.span.line, tok.span.col), (2, 1));
+        assert!(lex.errors.is_empty());
+        assert_eq!(lex.comments.len(), 1);
+        assert_eq!(lex.comments[0].text, "#!/usr/bin/env quokka");
+
+        //only the first line can be a shebang.
+        let mut lex = Lexer::new("let x = 5;\n#!/usr/bin/env quokka");
+        while lex.next_token().tok_type != TokenType::EOF {}
+        assert!(!lex.errors.is_empty());
+    }
+
+    #[test]
+    fn test_next_token_byte_order_mark() {
+        //the mark is skipped, spans still count its bytes but not a column.
+        let mut lex = Lexer::new("\u{feff}let x = 5;");
+        let tok = lex.next_token();
+        assert_eq!(tok.tok_type, TokenType::Let);
+        assert_eq!((tok.span.start, tok.span.line, tok.span.col), (3, 1, 1));
+        assert!(lex.errors.is_empty());
+
+        let mut lex = Lexer::new("\u{feff}#!/usr/bin/env quokka\nlet x = 5;");
+        let tok = lex.next_token();
+        assert_eq!(tok.tok_type, TokenType::Let);
+        assert_eq!((tok.span.line, tok.span.col), (2, 1));
+        assert_eq!(lex.comments[0].text, "#!/usr/bin/env quokka");
 
-        for (_, test_tup) in vec.iter().enumerate() {
+        //a mark anywhere else is an illegal character.
+        let mut lex = Lexer::new("1 \u{feff}");
+        while lex.next_token().tok_type != TokenType::EOF {}
+        assert!(!lex.errors.is_empty());
+    }
+
+    #[test]
+    fn test_next_token_unterminated_comment() {
+        let mut lex = Lexer::new("1 /* open /* nested */ never closed");
+        assert_eq!(lex.next_token().tok_type, TokenType::Int(1));
+        assert_eq!(lex.next_token().tok_type, TokenType::EOF);
+        assert_eq!(
+            error_messages(&lex),
+            vec!["unterminated block comment at 1:3".to_string()]
+        );
+        assert_eq!(lex.comments.len(), 1);
+    }
+
+    #[test]
+    fn test_next_token_floats() {
+        let input = "2.75 .5 1e-9 2.5E+3 7e2 10 1.x 2e";
+        let vec: Vec<(TokenType, &str)> = vec![
+            (TokenType::Float(2.75), "2.75"),
+            (TokenType::Float(0.5), ".5"),
+            (TokenType::Float(1e-9), "1e-9"),
+            (TokenType::Float(2500.0), "2.5E+3"),
+            (TokenType::Float(700.0), "7e2"),
+            (TokenType::Int(10), "10"),
+            (TokenType::Int(1), "1"),
+            (TokenType::Illegal, "."),
+            (TokenType::Ident, "x"),
+            (TokenType::Illegal, "2e"),
+            (TokenType::EOF, ""),
+        ];
+
+        let mut lex = Lexer::new(input);
+        for test_tup in vec.iter() {
             let tok = lex.next_token();
             assert_eq!(tok.tok_type, test_tup.0);
             assert_eq!(tok.literal, test_tup.1);
         }
+
+        let mut lex = Lexer::new("1_000.000_5 1e1_0");
+        assert_eq!(lex.next_token().tok_type, TokenType::Float(1000.0005));
+        assert_eq!(lex.next_token().tok_type, TokenType::Float(1e10));
+
+        let mut lex = Lexer::new("1e999");
+        assert_eq!(lex.next_token().tok_type, TokenType::Illegal);
+        assert_eq!(
+            error_messages(&lex),
+            vec!["float literal 1e999 is out of range at 1:1".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_next_token_int_range() {
+        let mut lex = Lexer::new("2147483648 9223372036854775807 9223372036854775808");
+        assert_eq!(lex.next_token().tok_type, TokenType::Int(2147483648));
+        assert_eq!(lex.next_token().tok_type, TokenType::Int(i64::MAX));
+        let tok = lex.next_token();
+        assert_eq!(tok.tok_type, TokenType::BigInt);
+        assert_eq!(tok.literal, "9223372036854775808");
+        assert!(lex.errors.is_empty());
+    }
+
+    #[test]
+    fn test_next_token_int_radix() {
+        let input =
+            "0xFF 0Xff 0b1010 0o755 1_000_000 0x_dead_beef 0b1111_0000 007 0xFFFFFFFFFFFFFFFF";
+        let vec: Vec<(TokenType, &str)> = vec![
+            (TokenType::Int(255), "0xFF"),
+            (TokenType::Int(255), "0Xff"),
+            (TokenType::Int(10), "0b1010"),
+            (TokenType::Int(493), "0o755"),
+            (TokenType::Int(1_000_000), "1_000_000"),
+            (TokenType::Illegal, "0x_dead_beef"),
+            (TokenType::Int(240), "0b1111_0000"),
+            (TokenType::Int(7), "007"),
+            (TokenType::BigInt, "18446744073709551615"),
+            (TokenType::EOF, ""),
+        ];
+
+        let mut lex = Lexer::new(input);
+        for test_tup in vec.iter() {
+            let tok = lex.next_token();
+            assert_eq!(tok.tok_type, test_tup.0);
+            assert_eq!(tok.literal, test_tup.1);
+        }
+    }
+
+    #[test]
+    fn test_next_token_malformed_numbers() {
+        let tests = vec![
+            ("0x", "missing digits in hexadecimal literal at 1:1"),
+            ("0b102", "invalid digit '2' in binary literal at 1:1"),
+            ("0o8", "invalid digit '8' in octal literal at 1:1"),
+            ("0xFG", "invalid digit 'G' in hexadecimal literal at 1:1"),
+            ("x = 12ab", "invalid digit 'a' in decimal literal at 1:5"),
+            ("1__000", "misplaced underscore in decimal literal at 1:1"),
+            ("1000_", "misplaced underscore in decimal literal at 1:1"),
+            ("1.5_", "misplaced underscore in decimal literal at 1:1"),
+            ("1e+", "missing digits in decimal literal at 1:1"),
+            (
+                "1\u{0663}",
+                "invalid digit '\u{0663}' in decimal literal at 1:1",
+            ),
+        ];
+
+        for (input, expected) in &tests {
+            let mut lex = Lexer::new(input);
+            let mut tok = lex.next_token();
+            while tok.tok_type != TokenType::EOF && tok.tok_type != TokenType::Illegal {
+                tok = lex.next_token();
+            }
+            assert_eq!(tok.tok_type, TokenType::Illegal, "input: {}", input);
+            assert!(!tok.literal.is_empty());
+            assert_eq!(
+                error_messages(&lex),
+                vec![expected.to_string()],
+                "input: {}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_next_token_identifiers() {
+        let input = "_tmp x1 snake_case _ café 变量 ünïcödé lettuce iffy fn_ let";
+        let vec: Vec<(TokenType, &str)> = vec![
+            (TokenType::Ident, "_tmp"),
+            (TokenType::Ident, "x1"),
+            (TokenType::Ident, "snake_case"),
+            (TokenType::Ident, "_"),
+            (TokenType::Ident, "café"),
+            (TokenType::Ident, "变量"),
+            (TokenType::Ident, "ünïcödé"),
+            (TokenType::Ident, "lettuce"),
+            (TokenType::Ident, "iffy"),
+            (TokenType::Ident, "fn_"),
+            (TokenType::Let, "let"),
+            (TokenType::EOF, ""),
+        ];
+
+        let mut lex = Lexer::new(input);
+        for test_tup in vec.iter() {
+            let tok = lex.next_token();
+            assert_eq!(tok.tok_type, test_tup.0);
+            assert_eq!(tok.literal, test_tup.1);
+        }
+        assert!(lex.errors.is_empty());
+    }
+
+    #[test]
+    fn test_next_token_invalid_ident_chars() {
+        let input = "a$b\nlet x€ = 1;";
+        let vec: Vec<(TokenType, &str)> = vec![
+            (TokenType::Ident, "a"),
+            (TokenType::Illegal, "$"),
+            (TokenType::Ident, "b"),
+            (TokenType::Let, "let"),
+            (TokenType::Ident, "x"),
+            (TokenType::Illegal, "€"),
+            (TokenType::Assign, "="),
+        ];
+
+        let mut lex = Lexer::new(input);
+        for test_tup in vec.iter() {
+            let tok = lex.next_token();
+            assert_eq!(tok.tok_type, test_tup.0);
+            assert_eq!(tok.literal, test_tup.1);
+        }
+        assert_eq!(
+            error_messages(&lex),
+            vec![
+                "illegal character '$' at 1:2".to_string(),
+                "illegal character '€' at 2:6".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_next_token_comparison_operators() {
+        let input = "a <= b >= c < =d >=";
+        let vec: Vec<(TokenType, &str)> = vec![
+            (TokenType::Ident, "a"),
+            (TokenType::LarrowEQ, "<="),
+            (TokenType::Ident, "b"),
+            (TokenType::RarrowEQ, ">="),
+            (TokenType::Ident, "c"),
+            (TokenType::Larrow, "<"),
+            (TokenType::Assign, "="),
+            (TokenType::Ident, "d"),
+            (TokenType::RarrowEQ, ">="),
+            (TokenType::EOF, ""),
+        ];
+
+        let mut lex = Lexer::new(input);
+        for test_tup in vec.iter() {
+            let tok = lex.next_token();
+            assert_eq!(tok.tok_type, test_tup.0);
+            assert_eq!(tok.literal, test_tup.1);
+        }
+    }
+
+    #[test]
+    fn test_next_token_logical_operators() {
+        let input = "a && b || !c";
+        let vec: Vec<(TokenType, &str)> = vec![
+            (TokenType::Ident, "a"),
+            (TokenType::And, "&&"),
+            (TokenType::Ident, "b"),
+            (TokenType::Or, "||"),
+            (TokenType::Not, "!"),
+            (TokenType::Ident, "c"),
+            (TokenType::EOF, ""),
+        ];
+
+        let mut lex = Lexer::new(input);
+        for test_tup in vec.iter() {
+            let tok = lex.next_token();
+            assert_eq!(tok.tok_type, test_tup.0);
+            assert_eq!(tok.literal, test_tup.1);
+        }
+    }
+
+    #[test]
+    fn test_next_token_arithmetic_and_bitwise_operators() {
+        let input = "a % b ** c * d & e | f ^ ~g << h >> i <= j";
+        let vec: Vec<(TokenType, &str)> = vec![
+            (TokenType::Ident, "a"),
+            (TokenType::Percent, "%"),
+            (TokenType::Ident, "b"),
+            (TokenType::Power, "**"),
+            (TokenType::Ident, "c"),
+            (TokenType::Asterisk, "*"),
+            (TokenType::Ident, "d"),
+            (TokenType::Ampersand, "&"),
+            (TokenType::Ident, "e"),
+            (TokenType::Pipe, "|"),
+            (TokenType::Ident, "f"),
+            (TokenType::Caret, "^"),
+            (TokenType::Tilde, "~"),
+            (TokenType::Ident, "g"),
+            (TokenType::LShift, "<<"),
+            (TokenType::Ident, "h"),
+            (TokenType::RShift, ">>"),
+            (TokenType::Ident, "i"),
+            (TokenType::LarrowEQ, "<="),
+            (TokenType::Ident, "j"),
+            (TokenType::EOF, ""),
+        ];
+
+        let mut lex = Lexer::new(input);
+        for test_tup in vec.iter() {
+            let tok = lex.next_token();
+            assert_eq!(tok.tok_type, test_tup.0);
+            assert_eq!(tok.literal, test_tup.1);
+        }
+    }
+
+    #[test]
+    fn test_next_token_assignment_operators() {
+        let input = "x = 1; x += 2; x -= 3; x *= 4; x /= 5; x %= 6; x ** 2; x == x";
+        let expected = vec![
+            (TokenType::Assign, "="),
+            (TokenType::PlusAssign, "+="),
+            (TokenType::MinusAssign, "-="),
+            (TokenType::AsteriskAssign, "*="),
+            (TokenType::FslashAssign, "/="),
+            (TokenType::PercentAssign, "%="),
+            (TokenType::Power, "**"),
+            (TokenType::EQ, "=="),
+        ];
+
+        let mut lex = Lexer::new(input);
+        for (tok_type, literal) in expected {
+            assert_eq!(lex.next_token().tok_type, TokenType::Ident);
+            let tok = lex.next_token();
+            assert_eq!(tok.tok_type, tok_type);
+            assert_eq!(tok.literal, literal);
+            lex.next_token();
+            if tok_type != TokenType::EQ {
+                assert_eq!(lex.next_token().tok_type, TokenType::Semicolon);
+            }
+        }
+        assert_eq!(lex.next_token().tok_type, TokenType::EOF);
+    }
+
+    #[test]
+    fn test_next_token_square_brackets() {
+        let input = "[1, a][0]";
+        let vec: Vec<(TokenType, &str)> = vec![
+            (TokenType::Lsquare, "["),
+            (TokenType::Int(1), "1"),
+            (TokenType::Comma, ","),
+            (TokenType::Ident, "a"),
+            (TokenType::Rsquare, "]"),
+            (TokenType::Lsquare, "["),
+            (TokenType::Int(0), "0"),
+            (TokenType::Rsquare, "]"),
+            (TokenType::EOF, ""),
+        ];
+
+        let mut lex = Lexer::new(input);
+        for test_tup in vec.iter() {
+            let tok = lex.next_token();
+            assert_eq!(tok.tok_type, test_tup.0);
+            assert_eq!(tok.literal, test_tup.1);
+        }
+    }
+
+    fn error_messages(lex: &Lexer) -> Vec<String> {
+        lex.errors.iter().map(|err| err.to_string()).collect()
     }
 }
@@ -0,0 +1,13 @@
+#![allow(non_snake_case)]
+#![allow(
+    clippy::needless_return,
+    clippy::module_inception,
+    clippy::upper_case_acronyms,
+    clippy::useless_vec
+)]
+
+pub mod AST;
+pub mod evaluator;
+pub mod lexer;
+pub mod parser;
+pub mod token;
@@ -1,34 +1,130 @@
-use crate::evaluator::eval::eval;
-use crate::evaluator::object::{Enviornment, Object};
-use crate::AST::ast::Program;
-use crate::{lexer::lexer::Lexer, parser::parser::Parser};
+#![allow(clippy::needless_return)]
+
+use quokka::evaluator::eval::{
+    eval, set_max_call_depth, set_stack_size, DEFAULT_MAX_CALL_DEPTH, STACK_BYTES_PER_CALL,
+};
+use quokka::evaluator::object::{Enviornment, Object};
+use quokka::parser::error::ParseError;
+use quokka::AST::ast::Program;
+use quokka::{lexer::lexer::Lexer, parser::parser::Parser};
+use std::env;
+use std::fs;
 use std::io::{self, Write};
+use std::process::ExitCode;
+use std::thread::{self, JoinHandle};
+
+//exit codes for scripts, from sysexits.h.
+const EXIT_PARSE_ERROR: u8 = 65;
+const EXIT_NO_INPUT: u8 = 66;
+const EXIT_RUNTIME_ERROR: u8 = 70;
+
+//the usual main thread stack, the interpreter never gets less even with a low call depth.
+const MIN_STACK_BYTES: usize = 8 * 1024 * 1024;
+//the most stack asked for however high the call depth is, deeper recursion than fits is
+//stopped by the stack limit instead.
+const MAX_STACK_BYTES: usize = 1024 * 1024 * 1024;
+
+//`quokka` starts the repl, `quokka script.qk [args...]` runs the script.
+fn main() -> ExitCode {
+    let max_depth = env::var("QUOKKA_MAX_CALL_DEPTH")
+        .ok()
+        .and_then(|depth| depth.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_MAX_CALL_DEPTH);
+    let args: Vec<String> = env::args().skip(1).collect();
+    //non tail calls recurse on the native stack, give the evaluator enough for the call depth limit.
+    //the stack is only reserved, pages the evaluator never reaches are never used. Where even
+    //that fails the interpreter runs with the usual stack.
+    let stack_size = max_depth
+        .saturating_mul(STACK_BYTES_PER_CALL)
+        .clamp(MIN_STACK_BYTES, MAX_STACK_BYTES);
+    let interpreter = spawn_interpreter(stack_size, max_depth, args.clone())
+        .or_else(|_| spawn_interpreter(MIN_STACK_BYTES, max_depth, args));
+    match interpreter {
+        Ok(handle) => return handle.join().expect("interpreter thread panicked"),
+        Err(err) => {
+            eprintln!("quokka: can't start the interpreter: {}", err);
+            return ExitCode::FAILURE;
+        }
+    }
+}
+
+fn spawn_interpreter(
+    stack_size: usize,
+    max_depth: usize,
+    args: Vec<String>,
+) -> io::Result<JoinHandle<ExitCode>> {
+    return thread::Builder::new()
+        .name("interpreter".to_string())
+        .stack_size(stack_size)
+        .spawn(move || {
+            set_max_call_depth(max_depth);
+            set_stack_size(stack_size);
+            match args.split_first() {
+                Some((path, script_args)) => run_script(path, script_args),
+                None => match start_repl() {
+                    Ok(()) => ExitCode::SUCCESS,
+                    Err(err) => {
+                        eprintln!("quokka: {}", err);
+                        ExitCode::FAILURE
+                    }
+                },
+            }
+        });
+}
 
-pub(crate) mod AST;
-pub(crate) mod evaluator;
-pub(crate) mod lexer;
-pub(crate) mod parser;
-pub(crate) mod token;
+//runs the whole file, the arguments after the path are given to the script as the `args` array.
+fn run_script(path: &str, args: &[String]) -> ExitCode {
+    let source = match fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(err) => {
+            eprintln!("quokka: can't read {}: {}", path, err);
+            return ExitCode::from(EXIT_NO_INPUT);
+        }
+    };
+    let mut parser = Parser::new(Lexer::new(&source));
+    let program = parser.parse_program();
+    if !parser.errors().is_empty() {
+        for err in &parser.errors {
+            eprintln!("{}:{}: {}", path, err.span, err.message);
+        }
+        return ExitCode::from(EXIT_PARSE_ERROR);
+    }
+
+    let mut env = Enviornment::new();
+    let args = args.iter().map(|arg| Object::String(arg.clone())).collect();
+    env.set("args".to_string(), &Object::Array(args));
+    let statments = program.map(|program| program.statments).unwrap_or_default();
+    for s in statments.iter() {
+        match eval(s, &mut env) {
+            Some(Object::Error(err)) => {
+                eprintln!("{}", err.traceback_in(path));
+                return ExitCode::from(EXIT_RUNTIME_ERROR);
+            }
+            //a top level return ends the script.
+            Some(Object::ReturnValue(_)) => break,
+            _ => {}
+        }
+    }
+    return ExitCode::SUCCESS;
+}
 
-fn main() -> io::Result<()> {
+fn start_repl() -> io::Result<()> {
     let mut input = String::new();
     let mut env = Enviornment::new();
 
     loop {
         print!(">> ");
-        io::stdout().flush().unwrap();
-        io::stdin().read_line(&mut input).unwrap();
-        let lex = Lexer::new(
-            &mut Lexer {
-                ch: ' ',
-                input: input.clone(),
-            },
-            input.clone(),
-        );
+        io::stdout().flush()?;
+        //nothing read means stdin was closed.
+        if io::stdin().read_line(&mut input)? == 0 {
+            println!();
+            return Ok(());
+        }
+        let lex = Lexer::new(&input);
         let mut parser = Parser::new(lex);
         let program = parser.parse_program();
 
-        if parser.errors().len() != 0 {
+        if !parser.errors().is_empty() {
             print_parser_errors(parser.errors);
             input.clear();
             continue;
@@ -43,10 +139,11 @@ fn main() -> io::Result<()> {
         {
             let evaluated = eval(s, &mut env);
             if evaluated.is_some() {
-                println!("{}", evaluated.clone().unwrap().to_string());
-                if let Object::Error(_) = evaluated.clone().unwrap() {
+                if let Object::Error(err) = evaluated.clone().unwrap() {
+                    println!("{}", err.traceback());
                     break;
                 }
+                println!("{}", evaluated.clone().unwrap());
                 if let Object::ReturnValue(_) = evaluated.clone().unwrap() {
                     break;
                 }
@@ -65,7 +162,7 @@ fn main() -> io::Result<()> {
     }
 }
 
-fn print_parser_errors(errors: Vec<String>) {
+fn print_parser_errors(errors: Vec<ParseError>) {
     let monkey = "     
             __,__
    .--.  .-'     '-.  .--.
@@ -0,0 +1,116 @@
+use crate::token::token::{Span, Token, TokenType};
+use std::fmt::Display;
+
+#[derive(Clone, PartialEq)]
+pub struct ParseError {
+    pub message: String,
+    pub span: Span,
+    //the token types that would have been accepted, empty when the error isn't a missing token.
+    pub expected: Vec<TokenType>,
+    //the offending token, an Illegal token for errors reported by the lexer.
+    pub found: Token,
+}
+
+impl ParseError {
+    pub fn new(message: String, found: &Token) -> ParseError {
+        ParseError {
+            message,
+            span: found.span,
+            expected: Vec::new(),
+            found: found.clone(),
+        }
+    }
+
+    pub fn expected(expected: Vec<TokenType>, found: &Token) -> ParseError {
+        let names: Vec<String> = expected.iter().map(describe_token_type).collect();
+        let names = match names.split_last() {
+            Some((last, [])) => last.clone(),
+            Some((last, rest)) => format!("{} or {}", rest.join(", "), last),
+            None => "nothing".to_string(),
+        };
+        ParseError {
+            message: format!("expected {}, found {}", names, describe_token(found)),
+            span: found.span,
+            expected,
+            found: found.clone(),
+        }
+    }
+}
+
+//how a token appeared in the source, for error messages.
+pub fn describe_token(tok: &Token) -> String {
+    match tok.tok_type {
+        TokenType::EOF => "end of input".to_string(),
+        TokenType::Str => format!("string '{}'", tok.literal),
+        _ if tok.literal.is_empty() => describe_token_type(&tok.tok_type),
+        _ => format!("'{}'", tok.literal),
+    }
+}
+
+//a kind of token as it's written in the source, quoted, or a name for tokens with no fixed
+//spelling.
+pub fn describe_token_type(tok_type: &TokenType) -> String {
+    let symbol = match tok_type {
+        TokenType::Ident => return "identifier".to_string(),
+        TokenType::Int(_) | TokenType::BigInt => return "integer".to_string(),
+        TokenType::Float(_) => return "float".to_string(),
+        TokenType::Str => return "string".to_string(),
+        TokenType::EOF => return "end of input".to_string(),
+        TokenType::Illegal => return "illegal token".to_string(),
+        TokenType::Plus => "+",
+        TokenType::Minus => "-",
+        TokenType::EQ => "==",
+        TokenType::NotEQ => "!=",
+        TokenType::Not => "!",
+        TokenType::Larrow => "<",
+        TokenType::Rarrow => ">",
+        TokenType::LarrowEQ => "<=",
+        TokenType::RarrowEQ => ">=",
+        TokenType::And => "&&",
+        TokenType::Or => "||",
+        TokenType::Percent => "%",
+        TokenType::Power => "**",
+        TokenType::Ampersand => "&",
+        TokenType::Pipe => "|",
+        TokenType::Caret => "^",
+        TokenType::Tilde => "~",
+        TokenType::LShift => "<<",
+        TokenType::RShift => ">>",
+        TokenType::PlusAssign => "+=",
+        TokenType::MinusAssign => "-=",
+        TokenType::AsteriskAssign => "*=",
+        TokenType::FslashAssign => "/=",
+        TokenType::PercentAssign => "%=",
+        TokenType::Fslash => "/",
+        TokenType::Assign => "=",
+        TokenType::Comma => ",",
+        TokenType::Colon => ":",
+        TokenType::Semicolon => ";",
+        TokenType::Lparen => "(",
+        TokenType::Rparen => ")",
+        TokenType::Lbrack => "{",
+        TokenType::Rbrack => "}",
+        TokenType::Lsquare => "[",
+        TokenType::Rsquare => "]",
+        TokenType::Asterisk => "*",
+        TokenType::Let => "let",
+        TokenType::Function => "fn",
+        TokenType::If => "if",
+        TokenType::Else => "else",
+        TokenType::True => "true",
+        TokenType::False => "false",
+        TokenType::Return => "return",
+        TokenType::While => "while",
+        TokenType::Break => "break",
+        TokenType::Continue => "continue",
+        TokenType::For => "for",
+        TokenType::In => "in",
+    };
+    return format!("'{}'", symbol);
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at {}", self.message, self.span)
+    }
+}
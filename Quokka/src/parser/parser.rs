@@ -1,29 +1,51 @@
-use crate::token::token::{Token, TokenType};
-use crate::Lexer;
+use super::error::{describe_token, ParseError};
+use crate::lexer::lexer::Lexer;
+use crate::token::token::{Span, Token, TokenType};
 use crate::AST::ast::{
-    BlockStatment, Boolen, CallExpression, Expression, FunctionLiteral, Identifier, IfStatment,
-    InfixExpression, IntLiteral, LetStatment, PrefixExpression, Program, ReturnStatment, Statment,
+    ArrayLiteral, AssignExpression, BigIntLiteral, BlockStatment, Boolen, BreakStatment,
+    CallExpression, ContinueStatment, ElseBranch, Expression, FloatLiteral, ForExpression,
+    FunctionLiteral, HashLiteral, Identifier, IfStatment, IndexExpression, Infix, InfixExpression,
+    IntLiteral, LetStatment, Prefix, PrefixExpression, Program, ReturnStatment, Statment,
+    StringLiteral, WhileExpression,
 };
 use c_enum::c_enum;
-use std::fmt::Write;
 
 #[derive(Clone)]
 pub struct Parser {
     pub lexer: Lexer,
     pub curr_token: Token,
     pub peek_token: Token,
-    pub errors: Vec<String>,
+    pub errors: Vec<ParseError>,
+    //number of loops enclosing the current token, break and continue are only valid inside one.
+    loop_depth: usize,
+    //number of `{` opened and not yet closed up to the current token, used by synchronize.
+    brace_depth: usize,
+    //number of nested parse calls in progress, see Parser::nested.
+    nesting: usize,
 }
 
+//the parser recurses once per nested expression or block, this keeps arbitrary input from
+//overflowing the native stack. The deepest case, an if in the condition of an if, takes about
+//8KB a level in a debug build, so the limit fits in the 2MB stack of a spawned thread.
+pub const MAX_NESTING_DEPTH: usize = 200;
+
 c_enum! {
 #[derive(PartialEq,PartialOrd, Clone, Copy, Eq)]
     pub enum Precedence :i32 {
         Lowest = 1,
+        Assign,      //= or +=
+        LogicalOr,   //||
+        LogicalAnd,  //&&
         Equals,      //==
         LessGreater, //< or >
+        BitOr,       //|
+        BitXor,      //^
+        BitAnd,      //&
+        Shift,       //<< or >>
         Sum,         // + or -
-        Product,     //*
+        Product,     //* / or %
         Prefix,
+        Exponent,    //**
         Call,        //func(x)
 
     }
@@ -34,12 +56,16 @@ impl Parser {
         let tok = Token {
             literal: "".to_string(),
             tok_type: TokenType::EOF,
+            span: Span::default(),
         };
         let mut p = Parser {
             lexer: lex,
             curr_token: tok.clone(),
             peek_token: tok.clone(),
             errors: vec![],
+            loop_depth: 0,
+            brace_depth: 0,
+            nesting: 0,
         };
         p.next_token_parser();
         p.next_token_parser();
@@ -53,15 +79,35 @@ impl Parser {
 
     fn token_to_precedence(tok: &TokenType) -> Precedence {
         match *tok {
+            TokenType::Assign
+            | TokenType::PlusAssign
+            | TokenType::MinusAssign
+            | TokenType::AsteriskAssign
+            | TokenType::FslashAssign
+            | TokenType::PercentAssign => Precedence::Assign,
+            TokenType::Or => Precedence::LogicalOr,
+            TokenType::And => Precedence::LogicalAnd,
             TokenType::EQ | TokenType::NotEQ => Precedence::Equals,
-            TokenType::Rarrow | TokenType::Larrow => Precedence::LessGreater,
+            TokenType::Rarrow | TokenType::Larrow | TokenType::RarrowEQ | TokenType::LarrowEQ => {
+                Precedence::LessGreater
+            }
+            TokenType::Pipe => Precedence::BitOr,
+            TokenType::Caret => Precedence::BitXor,
+            TokenType::Ampersand => Precedence::BitAnd,
+            TokenType::LShift | TokenType::RShift => Precedence::Shift,
             TokenType::Plus | TokenType::Minus => Precedence::Sum,
-            TokenType::Asterisk | TokenType::Fslash => Precedence::Product,
-            TokenType::Lparen => Precedence::Call,
+            TokenType::Asterisk | TokenType::Fslash | TokenType::Percent => Precedence::Product,
+            TokenType::Power => Precedence::Exponent,
+            TokenType::Lparen | TokenType::Lsquare => Precedence::Call,
             _ => Precedence::Lowest,
         }
     }
 
+    //span from `start` up to and including the current token.
+    fn span_from(&self, start: Span) -> Span {
+        start.to(&self.curr_token.span)
+    }
+
     fn next_token_precedence(&mut self) -> Precedence {
         Self::token_to_precedence(&self.peek_token.tok_type)
     }
@@ -72,8 +118,38 @@ impl Parser {
 
     pub fn next_token_parser(&mut self) {
         let tok = self.lexer.next_token();
+        for err in self.lexer.errors.drain(..) {
+            let mut error = ParseError::new(err.message, &tok);
+            error.span = err.span;
+            self.errors.push(error);
+        }
         self.curr_token = self.peek_token.clone();
         self.peek_token = tok;
+        match self.curr_token.tok_type {
+            TokenType::Lbrack => self.brace_depth += 1,
+            TokenType::Rbrack => self.brace_depth = self.brace_depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+
+    //panic mode recovery after a statment failed to parse, skips to the `;` ending it, or to
+    //just before the `}` closing the enclosing block, so parsing carries on with the next
+    //statment. depth is the brace depth of the enclosing block, braces opened by the failed
+    //statment itself are skipped over.
+    fn synchronize(&mut self, depth: usize) {
+        loop {
+            if self.brace_depth < depth || self.curr_token_is(&TokenType::EOF) {
+                return;
+            }
+            if self.brace_depth == depth
+                && (self.curr_token_is(&TokenType::Semicolon)
+                    || self.next_token_is(&TokenType::Rbrack)
+                    || self.next_token_is(&TokenType::EOF))
+            {
+                return;
+            }
+            self.next_token_parser();
+        }
     }
 
     pub fn parse_program(&mut self) -> Option<Program> {
@@ -82,8 +158,9 @@ impl Parser {
         };
 
         while self.curr_token.tok_type != TokenType::EOF {
-            if let Some(statment) = self.parse_statment(self.curr_token.clone()) {
-                program.statments.push(statment);
+            match self.parse_statment(self.curr_token.clone()) {
+                Some(statment) => program.statments.push(statment),
+                None => self.synchronize(0),
             }
             self.next_token_parser();
         }
@@ -94,6 +171,8 @@ impl Parser {
         return match curr_tok.tok_type {
             TokenType::Let => self.parse_let_statment(),
             TokenType::Return => self.parse_return_statments(),
+            TokenType::Break | TokenType::Continue => self.parse_loop_control_statment(),
+            //already reported by the lexer.
             TokenType::Illegal => None,
             _ => self.parse_expr_statments(),
         };
@@ -111,7 +190,7 @@ impl Parser {
         };
     }
 
-    fn parse_bool_expr(&mut self) -> Expression {
+    fn parse_bool_expr(&mut self) -> Option<Expression> {
         let bol = Boolen {
             tok_type: if self.curr_token_is(&TokenType::True) {
                 TokenType::True
@@ -119,289 +198,540 @@ impl Parser {
                 TokenType::False
             },
             value: self.curr_token_is(&TokenType::True),
+            span: self.curr_token.span,
         };
-        Expression::BoolenExpr(bol)
+        Some(Expression::BoolenExpr(bol))
     }
 
     fn parse_expr(&mut self, prec: Precedence) -> Option<Expression> {
+        self.nested(|p| p.parse_nested_expr(prec))
+    }
+
+    //runs a parse function that may recurse, failing once MAX_NESTING_DEPTH are in progress.
+    //every nested construct recurses through parse_expr or a block, which both use this, and
+    //infix chains take a level per step with deeper.
+    fn nested<T>(&mut self, parse: impl FnOnce(&mut Self) -> Option<T>) -> Option<T> {
+        self.deeper()?;
+        let result = parse(self);
+        self.nesting -= 1;
+        result
+    }
+
+    //takes one more level of nesting, the caller gives it back.
+    fn deeper(&mut self) -> Option<()> {
+        if self.nesting == MAX_NESTING_DEPTH {
+            let message = format!(
+                "expression nested too deeply, the limit is {}",
+                MAX_NESTING_DEPTH
+            );
+            self.errors.push(ParseError::new(message, &self.curr_token));
+            return None;
+        }
+        self.nesting += 1;
+        return Some(());
+    }
+
+    fn parse_nested_expr(&mut self, prec: Precedence) -> Option<Expression> {
         // prefix
-        let mut lhs = match self.curr_token.tok_type {
+        let lhs = match self.curr_token.tok_type {
             TokenType::Ident => self.parse_ident(),
             TokenType::Int(num) => self.parse_int(num),
-            TokenType::Not => self.parse_prefix_expr(),
-            TokenType::Minus => self.parse_prefix_expr(),
-            TokenType::Plus => self.parse_prefix_expr(),
+            TokenType::BigInt => self.parse_big_int(),
+            TokenType::Float(num) => self.parse_float(num),
+            TokenType::Str => self.parse_string(),
+            TokenType::Not => self.parse_prefix_expr(Prefix::Not),
+            TokenType::Minus => self.parse_prefix_expr(Prefix::Minus),
+            TokenType::Plus => self.parse_prefix_expr(Prefix::Plus),
+            TokenType::Tilde => self.parse_prefix_expr(Prefix::BitNot),
             TokenType::True | TokenType::False => self.parse_bool_expr(),
             TokenType::Lparen => self.parse_grouped_expr(),
-            TokenType::If => self.parse_if_expr()?,
-            TokenType::Function => self.parse_func_literal()?,
+            TokenType::Lsquare => self.parse_array_literal(),
+            //blocks are only parsed after if, while, for and fn, so here `{` starts a hash.
+            TokenType::Lbrack => self.parse_hash_literal(),
+            TokenType::If => self.parse_if_expr(),
+            TokenType::Function => self.parse_func_literal(),
+            TokenType::While => self.parse_while_expr(),
+            TokenType::For => self.parse_for_expr(),
+            //already reported by the lexer.
+            TokenType::Illegal => None,
             _ => self.prefix_error(),
-        };
-
-        //infix
+        }?;
+
+        //every call, index or operator wraps lhs one level deeper, so each counts as a level of
+        //nesting until the whole chain is parsed. Otherwise `a[0][0]...` builds a tree too deep
+        //to evaluate or even drop.
+        let nesting = self.nesting;
+        let result = self.parse_infix_chain(lhs, prec);
+        self.nesting = nesting;
+        result
+    }
 
+    fn parse_infix_chain(&mut self, mut lhs: Expression, prec: Precedence) -> Option<Expression> {
         while !self.next_token_is(&TokenType::Semicolon) && prec < self.next_token_precedence() {
             match self.peek_token.tok_type {
-                TokenType::Plus
-                | TokenType::Minus
-                | TokenType::Fslash
-                | TokenType::Asterisk
-                | TokenType::EQ
-                | TokenType::NotEQ
-                | TokenType::Larrow
-                | TokenType::Rarrow => {
+                TokenType::Lparen => {
                     self.next_token_parser();
-                    lhs = self.parse_infix_expr(&lhs);
+                    self.deeper()?;
+                    lhs = self.parse_call_expr(lhs)?;
                 }
-                TokenType::Lparen => {
+                TokenType::Lsquare => {
                     self.next_token_parser();
-                    lhs = self.parse_call_expr(&lhs);
+                    self.deeper()?;
+                    lhs = self.parse_index_expr(lhs)?;
                 }
-                _ => return Some(lhs),
+                TokenType::Assign
+                | TokenType::PlusAssign
+                | TokenType::MinusAssign
+                | TokenType::AsteriskAssign
+                | TokenType::FslashAssign
+                | TokenType::PercentAssign => {
+                    self.next_token_parser();
+                    self.deeper()?;
+                    lhs = self.parse_assign_expr(&lhs)?;
+                }
+                tok => match Infix::from_token(&tok) {
+                    Some(op) => {
+                        self.next_token_parser();
+                        self.deeper()?;
+                        lhs = self.parse_infix_expr(lhs, op)?;
+                    }
+                    None => return Some(lhs),
+                },
             };
         }
         Some(lhs)
     }
 
-    fn parse_call_expr(&mut self, func: &Expression) -> Expression {
+    fn parse_call_expr(&mut self, func: Expression) -> Option<Expression> {
+        let arguments = self.parse_call_arguments()?;
+        let span = self.span_from(func.span());
         let expr = CallExpression {
-            arguments: self.parse_call_arguments(),
-            function: func.clone(),
+            arguments,
+            function: func,
+            span,
         };
-        Expression::Call(Box::new(expr))
+        Some(Expression::Call(Box::new(expr)))
     }
 
-    fn parse_call_arguments(&mut self) -> Vec<Expression> {
-        let mut args = Vec::new();
-        if self.next_token_is(&TokenType::Rparen) {
-            self.next_token_parser();
-            return args;
-        }
-        self.next_token_parser();
-        args.push(
-            self.parse_expr(Precedence::Lowest)
-                .unwrap_or(Expression::Blank),
-        );
+    fn parse_call_arguments(&mut self) -> Option<Vec<Expression>> {
+        self.parse_expr_list(TokenType::Rparen)
+    }
 
-        while !self.next_token_is(&TokenType::Rparen) {
+    fn parse_array_literal(&mut self) -> Option<Expression> {
+        let start = self.curr_token.span;
+        let elements = self.parse_expr_list(TokenType::Rsquare)?;
+        Some(Expression::Array(ArrayLiteral {
+            elements,
+            span: self.span_from(start),
+        }))
+    }
+
+    fn parse_hash_literal(&mut self) -> Option<Expression> {
+        let start = self.curr_token.span;
+        let mut pairs = Vec::new();
+        while !self.next_token_is(&TokenType::Rbrack) {
             self.next_token_parser();
+            let key = self.parse_expr(Precedence::Lowest)?;
+            self.expect_peek(TokenType::Colon)?;
             self.next_token_parser();
-            args.push(
-                self.parse_expr(Precedence::Lowest)
-                    .unwrap_or(Expression::Blank),
-            );
+            let value = self.parse_expr(Precedence::Lowest)?;
+            pairs.push((key, value));
+
+            if self.next_token_is(&TokenType::Comma) {
+                self.next_token_parser();
+            } else if !self.next_token_is(&TokenType::Rbrack) {
+                self.peek_error(vec![TokenType::Comma, TokenType::Rbrack]);
+                return None;
+            }
         }
-        if self.next_token_is(&TokenType::Rparen) {
+        self.next_token_parser();
+        Some(Expression::Hash(HashLiteral {
+            pairs,
+            span: self.span_from(start),
+        }))
+    }
+
+    fn parse_index_expr(&mut self, lhs: Expression) -> Option<Expression> {
+        self.next_token_parser();
+        let index = self.parse_expr(Precedence::Lowest)?;
+        self.expect_peek(TokenType::Rsquare)?;
+        let span = self.span_from(lhs.span());
+        let expr = IndexExpression { lhs, index, span };
+        Some(Expression::Index(Box::new(expr)))
+    }
+
+    //comma separated expressions, the current token is the opening delimiter. Like every comma
+    //separated list, hashes and parameters included, it may end with a trailing comma.
+    fn parse_expr_list(&mut self, end: TokenType) -> Option<Vec<Expression>> {
+        let mut list = Vec::new();
+        while !self.next_token_is(&end) {
             self.next_token_parser();
+            list.push(self.parse_expr(Precedence::Lowest)?);
+
+            if self.next_token_is(&TokenType::Comma) {
+                self.next_token_parser();
+            } else if !self.next_token_is(&end) {
+                self.peek_error(vec![TokenType::Comma, end]);
+                return None;
+            }
         }
-        return args;
+        self.next_token_parser();
+        return Some(list);
     }
 
     fn parse_func_literal(&mut self) -> Option<Expression> {
+        let start = self.curr_token.span;
         let mut lit = FunctionLiteral {
+            name: String::new(),
             params: Vec::new(),
-            body: BlockStatment { stmts: Vec::new() },
+            body: BlockStatment {
+                stmts: Vec::new(),
+                span: Span::default(),
+            },
+            span: start,
         };
-        if !self.next_token_is(&TokenType::Lparen) {
-            return None;
-        }
-        self.next_token_parser();
-        lit.params = self.parse_func_param().unwrap_or(Vec::new());
-
-        if !self.next_token_is(&TokenType::Lbrack) {
-            return None;
-        }
-        self.next_token_parser();
-        lit.body = *self.parse_block_statment();
+        self.expect_peek(TokenType::Lparen)?;
+        lit.params = self.parse_func_param()?;
+        self.expect_peek(TokenType::Lbrack)?;
+        //a function body starts outside of any loop, even when defined inside one.
+        let loop_depth = std::mem::replace(&mut self.loop_depth, 0);
+        let body = self.parse_block_statment();
+        self.loop_depth = loop_depth;
+        lit.body = *body?;
+        lit.span = self.span_from(start);
         return Some(Expression::Func(lit));
     }
 
     fn parse_func_param(&mut self) -> Option<Vec<Identifier>> {
         let mut identifiers = Vec::new();
-        if self.next_token_is(&TokenType::Rparen) {
-            self.next_token_parser();
-            return None;
-        }
-        self.next_token_parser();
-        let ident = Identifier {
-            value: self.curr_token.literal.clone(),
-        };
-        identifiers.push(ident);
+        while !self.next_token_is(&TokenType::Rparen) {
+            self.expect_peek(TokenType::Ident)?;
+            identifiers.push(self.curr_ident());
 
-        while self.next_token_is(&TokenType::Comma) {
-            self.next_token_parser();
-            self.next_token_parser();
-            let ident = Identifier {
-                value: self.curr_token.literal.clone(),
-            };
-            identifiers.push(ident);
-        }
-        if !self.next_token_is(&TokenType::Rparen) {
-            return None;
+            if self.next_token_is(&TokenType::Comma) {
+                self.next_token_parser();
+            } else if !self.next_token_is(&TokenType::Rparen) {
+                self.peek_error(vec![TokenType::Comma, TokenType::Rparen]);
+                return None;
+            }
         }
         self.next_token_parser();
         return Some(identifiers);
     }
 
     fn parse_if_expr(&mut self) -> Option<Expression> {
-        let consq_block = BlockStatment { stmts: Vec::new() };
-
-        let mut expr = IfStatment {
-            condition: Expression::Blank,
-            consequence: consq_block,
-            alternative: None,
-        };
-        if self.peek_token.tok_type != TokenType::Lparen {
-            return None;
-        }
-        self.next_token_parser();
-        self.next_token_parser();
-        expr.condition = self.parse_expr(Precedence::Lowest)?;
-        if self.peek_token.tok_type != TokenType::Rparen {
-            return None;
-        }
-        self.next_token_parser();
-        if self.peek_token.tok_type != TokenType::Lbrack {
-            return None;
-        }
-        self.next_token_parser();
-        expr.consequence = *self.parse_block_statment();
+        let stmt = self.parse_if_statment()?;
+        return Some(Expression::If(Box::new(stmt)));
+    }
 
-        if self.next_token_is(&TokenType::Else) {
+    //the branches of an else if chain follow each other in the source, so they're parsed in a
+    //loop and don't count towards MAX_NESTING_DEPTH.
+    fn parse_if_statment(&mut self) -> Option<IfStatment> {
+        let mut branches = Vec::new();
+        let mut last = None;
+        loop {
+            let start = self.curr_token.span;
+            self.expect_peek(TokenType::Lparen)?;
             self.next_token_parser();
-            if self.peek_token.tok_type != TokenType::Lbrack {
-                return None;
+            let condition = self.parse_expr(Precedence::Lowest)?;
+            self.expect_peek(TokenType::Rparen)?;
+            self.expect_peek(TokenType::Lbrack)?;
+            let consequence = *self.parse_block_statment()?;
+            branches.push(IfStatment {
+                condition,
+                consequence,
+                alternative: None,
+                span: start,
+            });
+
+            if !self.next_token_is(&TokenType::Else) {
+                break;
             }
             self.next_token_parser();
-            expr.alternative = Some(*self.parse_block_statment());
+            if self.next_token_is(&TokenType::If) {
+                self.next_token_parser();
+            } else if self.next_token_is(&TokenType::Lbrack) {
+                self.next_token_parser();
+                last = Some(ElseBranch::Block(*self.parse_block_statment()?));
+                break;
+            } else {
+                self.peek_error(vec![TokenType::If, TokenType::Lbrack]);
+                return None;
+            }
         }
 
-        return Some(Expression::If(Box::new(expr)));
+        //every if in the chain spans to the end of the whole chain.
+        let end = self.curr_token.span;
+        for branch in branches.iter_mut() {
+            branch.span = branch.span.to(&end);
+        }
+        return IfStatment::chain(branches, last);
+    }
+
+    fn parse_while_expr(&mut self) -> Option<Expression> {
+        let start = self.curr_token.span;
+        self.expect_peek(TokenType::Lparen)?;
+        self.next_token_parser();
+        let condition = self.parse_expr(Precedence::Lowest)?;
+        self.expect_peek(TokenType::Rparen)?;
+        self.expect_peek(TokenType::Lbrack)?;
+        self.loop_depth += 1;
+        let body = self.parse_block_statment();
+        self.loop_depth -= 1;
+        let body = *body?;
+
+        let expr = WhileExpression {
+            condition,
+            body,
+            span: self.span_from(start),
+        };
+        return Some(Expression::While(Box::new(expr)));
     }
 
-    fn parse_block_statment(&mut self) -> Box<BlockStatment> {
-        let mut block = BlockStatment { stmts: Vec::new() };
+    fn parse_for_expr(&mut self) -> Option<Expression> {
+        let start = self.curr_token.span;
+        self.expect_peek(TokenType::Lparen)?;
+        self.expect_peek(TokenType::Ident)?;
+        let ident = self.curr_ident();
+        self.expect_peek(TokenType::In)?;
+        self.next_token_parser();
+        let iterable = self.parse_expr(Precedence::Lowest)?;
+        self.expect_peek(TokenType::Rparen)?;
+        self.expect_peek(TokenType::Lbrack)?;
+        self.loop_depth += 1;
+        let body = self.parse_block_statment();
+        self.loop_depth -= 1;
+        let body = *body?;
+
+        let expr = ForExpression {
+            ident,
+            iterable,
+            body,
+            span: self.span_from(start),
+        };
+        return Some(Expression::For(Box::new(expr)));
+    }
+
+    //statments that fail to parse are reported and skipped, only a missing `}` fails the block.
+    fn parse_block_statment(&mut self) -> Option<Box<BlockStatment>> {
+        self.nested(|p| p.parse_nested_block())
+    }
+
+    fn parse_nested_block(&mut self) -> Option<Box<BlockStatment>> {
+        let start = self.curr_token.span;
+        let depth = self.brace_depth;
+        let mut block = BlockStatment {
+            stmts: Vec::new(),
+            span: start,
+        };
         self.next_token_parser();
         while !self.curr_token_is(&TokenType::Rbrack) && !self.curr_token_is(&TokenType::EOF) {
-            let stmt = self.parse_statment(self.curr_token.clone());
-            if stmt.is_some() {
-                block
-                    .stmts
-                    .push(stmt.unwrap_or(Statment::Expr(Expression::Blank)));
+            match self.parse_statment(self.curr_token.clone()) {
+                Some(stmt) => block.stmts.push(stmt),
+                None => {
+                    self.synchronize(depth);
+                    //the failed statment ran into the `}` closing this block.
+                    if self.brace_depth < depth {
+                        break;
+                    }
+                }
             }
             self.next_token_parser();
         }
-        return Box::new(block);
+        if self.curr_token_is(&TokenType::EOF) {
+            self.errors.push(ParseError::expected(
+                vec![TokenType::Rbrack],
+                &self.curr_token,
+            ));
+            return None;
+        }
+        block.span = self.span_from(start);
+        return Some(Box::new(block));
     }
 
-    fn parse_grouped_expr(&mut self) -> Expression {
+    fn parse_grouped_expr(&mut self) -> Option<Expression> {
         self.next_token_parser();
-        let expr = self.parse_expr(Precedence::Lowest);
-        if self.peek_token.tok_type != TokenType::Rparen {
-            return Expression::Blank;
-        }
-        self.next_token_parser();
-
-        return expr.unwrap_or(Expression::Blank);
+        let expr = self.parse_expr(Precedence::Lowest)?;
+        self.expect_peek(TokenType::Rparen)?;
+        return Some(expr);
     }
 
-    fn parse_infix_expr(&mut self, left: &Expression) -> Expression {
+    fn parse_infix_expr(&mut self, left: Expression, op: Infix) -> Option<Expression> {
         let curr_expr = Expression::Blank;
+        let start = left.span();
         let mut infix = InfixExpression {
-            tok_type: self.curr_token.tok_type,
-            operator: self.curr_token.literal.clone(),
-            lhs: left.clone(),
+            operator: op,
+            lhs: left,
             rhs: curr_expr,
+            span: start,
         };
-        let prec = Self::token_to_precedence(&self.curr_token.tok_type);
+        let mut prec = Self::token_to_precedence(&self.curr_token.tok_type);
+        //right associative, `2 ** 3 ** 2` is `2 ** (3 ** 2)`.
+        if op == Infix::Power {
+            prec = Precedence::from(i32::from(prec) - 1);
+        }
+        self.next_token_parser();
+        //the operator already took a level for the rhs, a right associative chain like
+        //`1 ** 1 ** ...` is one level per operand.
+        infix.rhs = self.parse_nested_expr(prec)?;
+        infix.span = self.span_from(start);
+        Some(Expression::Infix(Box::new(infix)))
+    }
+
+    fn parse_assign_expr(&mut self, target: &Expression) -> Option<Expression> {
+        let operator = match self.curr_token.tok_type {
+            TokenType::PlusAssign => Some(Infix::Plus),
+            TokenType::MinusAssign => Some(Infix::Minus),
+            TokenType::AsteriskAssign => Some(Infix::Multiply),
+            TokenType::FslashAssign => Some(Infix::Divide),
+            TokenType::PercentAssign => Some(Infix::Modulo),
+            _ => None,
+        };
+        let assign_tok = self.curr_token.clone();
         self.next_token_parser();
-        infix.rhs = self.parse_expr(prec).unwrap_or(Expression::Blank);
-        Expression::Infix(Box::new(infix))
+        //right associative, `a = b = 1` assigns 1 to both.
+        let value = self.parse_expr(Precedence::Lowest)?;
+
+        let ident = match target {
+            Expression::Identifier(ident) => ident.clone(),
+            _ => {
+                let message = format!(
+                    "invalid assignment target: {}, only identifiers can be assigned",
+                    target
+                );
+                self.errors.push(ParseError::new(message, &assign_tok));
+                return None;
+            }
+        };
+        let expr = AssignExpression {
+            ident,
+            operator,
+            value,
+            span: self.span_from(target.span()),
+        };
+        Some(Expression::Assign(Box::new(expr)))
     }
 
-    fn parse_prefix_expr(&mut self) -> Expression {
+    fn parse_prefix_expr(&mut self, op: Prefix) -> Option<Expression> {
         let current_expr = Expression::Blank;
         let mut prefix_expr = PrefixExpression {
-            tok_type: self.curr_token.tok_type,
-            operator: self.curr_token.literal.clone(),
+            operator: op,
             rhs: current_expr,
+            span: self.curr_token.span,
         };
 
         self.next_token_parser();
-        prefix_expr.rhs = self
-            .parse_expr(Precedence::Prefix)
-            .unwrap_or(Expression::Blank);
-        return Expression::Prefix(Box::new(prefix_expr));
+        prefix_expr.rhs = self.parse_expr(Precedence::Prefix)?;
+        prefix_expr.span = self.span_from(prefix_expr.span);
+        return Some(Expression::Prefix(Box::new(prefix_expr)));
     }
 
-    fn parse_int(&mut self, num: i32) -> Expression {
-        let expr = Expression::Int(IntLiteral { value: num });
-        expr
+    fn parse_int(&mut self, num: i64) -> Option<Expression> {
+        Some(Expression::Int(IntLiteral {
+            value: num,
+            span: self.curr_token.span,
+        }))
     }
 
-    fn parse_ident(&mut self) -> Expression {
-        let ident = self.curr_token.literal.clone();
-        let expr = Expression::Identifier(Identifier {
-            value: ident.to_string(),
-        });
-        expr
+    fn parse_big_int(&mut self) -> Option<Expression> {
+        match self.curr_token.literal.parse() {
+            Ok(value) => Some(Expression::BigInt(BigIntLiteral {
+                value,
+                span: self.curr_token.span,
+            })),
+            Err(_) => {
+                let message = format!("invalid integer literal {}", self.curr_token.literal);
+                self.errors.push(ParseError::new(message, &self.curr_token));
+                None
+            }
+        }
+    }
+
+    fn parse_float(&mut self, num: f64) -> Option<Expression> {
+        Some(Expression::Float(FloatLiteral {
+            value: num,
+            span: self.curr_token.span,
+        }))
+    }
+
+    fn parse_string(&mut self) -> Option<Expression> {
+        Some(Expression::Str(StringLiteral {
+            value: self.curr_token.literal.clone(),
+            span: self.curr_token.span,
+        }))
+    }
+
+    fn parse_ident(&mut self) -> Option<Expression> {
+        Some(Expression::Identifier(self.curr_ident()))
+    }
+
+    fn curr_ident(&self) -> Identifier {
+        Identifier {
+            value: self.curr_token.literal.clone(),
+            span: self.curr_token.span,
+        }
     }
 
-    fn prefix_error(&mut self) -> Expression {
-        let s = "Prefix is incorrect, no prefix function to parse current prefix. got: "
-            .to_string()
-            + &self.curr_token.literal.to_string();
-        self.errors.push(s);
-        Expression::Blank
+    //the current token can't start an expression.
+    fn prefix_error(&mut self) -> Option<Expression> {
+        let message = format!(
+            "expected an expression, found {}",
+            describe_token(&self.curr_token)
+        );
+        self.errors.push(ParseError::new(message, &self.curr_token));
+        None
     }
 
     fn parse_let_statment(&mut self) -> Option<Statment> {
+        let start = self.curr_token.span;
         let mut stmt = LetStatment {
             ident: Identifier {
                 value: "".to_string(),
+                span: Span::default(),
             },
             value: Expression::Blank,
+            span: start,
         };
-        if !self.next_token_is(&TokenType::Ident) {
-            self.next_token_parser();
-            return None;
-        }
-        self.next_token_parser();
-
-        stmt.ident = Identifier {
-            value: self.curr_token.literal.clone(),
-        };
-        if !self.next_token_is(&TokenType::Assign) {
-            self.next_token_parser();
-            return None;
-        }
-        self.next_token_parser();
+        self.expect_peek(TokenType::Ident)?;
+        stmt.ident = self.curr_ident();
+        self.expect_peek(TokenType::Assign)?;
         self.next_token_parser();
 
         stmt.value = self.parse_expr(Precedence::Lowest)?;
+        if let Expression::Func(f) = &mut stmt.value {
+            f.name = stmt.ident.value.clone();
+        }
         if self.next_token_is(&TokenType::Semicolon) {
             self.next_token_parser();
         }
+        stmt.span = self.span_from(start);
         Some(Statment::Let(stmt))
     }
 
-    pub fn errors(&self) -> Vec<String> {
+    pub fn errors(&self) -> Vec<ParseError> {
         return self.errors.clone();
     }
 
-    pub fn peek_error(&mut self, tok: TokenType) {
-        let mut message: String = String::new();
-        write!(
-            message,
-            "Expected next token: {}, got: {}",
-            tok, self.peek_token.tok_type
-        );
-        self.errors.push(message);
+    pub fn peek_error(&mut self, expected: Vec<TokenType>) {
+        let error = ParseError::expected(expected, &self.peek_token);
+        self.errors.push(error);
+    }
+
+    //moves to the next token when it's of the expected type, otherwise reports it.
+    fn expect_peek(&mut self, tok: TokenType) -> Option<()> {
+        if !self.next_token_is(&tok) {
+            self.peek_error(vec![tok]);
+            return None;
+        }
+        self.next_token_parser();
+        Some(())
     }
 
     pub fn parse_return_statments(&mut self) -> Option<Statment> {
+        let start = self.curr_token.span;
         let mut stmt = ReturnStatment {
             return_value: Expression::Blank,
+            span: start,
         };
 
         self.next_token_parser();
@@ -410,6 +740,23 @@ impl Parser {
         if self.next_token_is(&TokenType::Semicolon) {
             self.next_token_parser();
         }
+        stmt.span = self.span_from(start);
         Some(Statment::Return(stmt))
     }
+
+    fn parse_loop_control_statment(&mut self) -> Option<Statment> {
+        let span = self.curr_token.span;
+        let is_break = self.curr_token_is(&TokenType::Break);
+        if self.loop_depth == 0 {
+            let message = format!("{} outside of a loop", self.curr_token.literal);
+            self.errors.push(ParseError::new(message, &self.curr_token));
+        }
+        if self.next_token_is(&TokenType::Semicolon) {
+            self.next_token_parser();
+        }
+        if is_break {
+            return Some(Statment::Break(BreakStatment { span }));
+        }
+        Some(Statment::Continue(ContinueStatment { span }))
+    }
 }
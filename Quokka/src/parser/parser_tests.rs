@@ -1,9 +1,10 @@
 #[cfg(test)]
 mod test {
     use crate::lexer::lexer::*;
+    use crate::parser::error::ParseError;
     use crate::parser::parser::Parser;
-    use crate::token::token::TokenType;
-    use crate::AST::ast::{Expression, Statment};
+    use crate::token::token::{Span, TokenType};
+    use crate::AST::ast::{ElseBranch, Expression, Infix, Statment};
     use castaway::cast;
     use std::panic;
 
@@ -19,7 +20,7 @@ mod test {
             T: Matchable + 'static,
         {
             let x = cast!(expected, i32);
-            test_int_lit(expr, x.unwrap_or(0));
+            test_int_lit(expr, x.unwrap_or(0).into());
         }
     }
 
@@ -78,11 +79,7 @@ mod test {
         ];
 
         for t_case in &tests {
-            let mut l = Lexer {
-                ch: 'l',
-                input: t_case.inp.to_string(),
-            };
-            let lex = Lexer::new(&mut l, t_case.inp.to_string());
+            let lex = Lexer::new(t_case.inp);
             let mut prsr = Parser::new(lex);
 
             let program = prsr.parse_program();
@@ -98,25 +95,17 @@ mod test {
             }
 
             let stmt = &program.clone().unwrap().statments[0];
-            test_let_helper(&stmt, t_case.expected_ident);
+            test_let_helper(stmt, t_case.expected_ident);
 
             if let Statment::Let(stmt) = &program.unwrap().statments[0] {
                 let val = &stmt.value;
 
                 match t_case.expected_value {
-                    Dtype::Str(s) => {
-                        test_lit_expr(&val, s);
-                        return;
-                    }
-                    Dtype::Int(i) => {
-                        test_lit_expr(&val, i);
-                        return;
-                    }
-                    Dtype::Bool(b) => {
-                        test_lit_expr(&val, b);
-                        return;
-                    }
+                    Dtype::Str(s) => test_lit_expr(val, s),
+                    Dtype::Int(i) => test_lit_expr(val, i),
+                    Dtype::Bool(b) => test_lit_expr(val, b),
                 };
+                continue;
             }
             panic!("Statment is not a Let statment");
         }
@@ -125,15 +114,15 @@ mod test {
         if let Statment::Let(st) = stmt {
             assert_eq!(st.ident.value, ident);
             if let Expression::Literal(lit) = &st.value {
-                assert!(lit.value.trim().parse::<i32>().is_ok());
+                assert!(lit.value.trim().parse::<i64>().is_ok());
             }
             return;
         }
         panic!("Statment isn't a let statment. @ test_let_helper");
     }
 
-    fn check_parser_errors(err: Vec<String>) {
-        if err.len() == 0 {
+    fn check_parser_errors(err: Vec<ParseError>) {
+        if err.is_empty() {
             return;
         }
 
@@ -152,11 +141,7 @@ mod test {
         return 10;
         return 9954;";
 
-        let mut l = Lexer {
-            ch: 'l',
-            input: input.to_string(),
-        };
-        let lex = Lexer::new(&mut l, input.to_string());
+        let lex = Lexer::new(input);
         let mut prsr = Parser::new(lex);
         let program = prsr.parse_program();
         if program.is_none() {
@@ -176,11 +161,7 @@ mod test {
         let input = "
         foobar;
         ";
-        let mut l = Lexer {
-            ch: 'f',
-            input: input.to_string(),
-        };
-        let lex = Lexer::new(&mut l, input.to_string());
+        let lex = Lexer::new(input);
         let mut prsr = Parser::new(lex);
 
         let program = prsr.parse_program();
@@ -211,11 +192,7 @@ mod test {
     #[test]
     fn test_bool_expr() {
         let input = "true;";
-        let mut l = Lexer {
-            ch: 't',
-            input: input.to_string(),
-        };
-        let lex = Lexer::new(&mut l, input.to_string());
+        let lex = Lexer::new(input);
         let mut prsr = Parser::new(lex);
 
         let program = prsr.parse_program();
@@ -232,7 +209,7 @@ mod test {
 
         if let Statment::Expr(expr_stmt) = &program.unwrap().statments[0] {
             if let Expression::BoolenExpr(bool_expr) = &expr_stmt {
-                if bool_expr.value != true {
+                if !bool_expr.value {
                     panic!(
                         "Boolen expresion isn't correct, expected: {}, got: {}",
                         true, bool_expr.value
@@ -257,11 +234,7 @@ mod test {
         let input = "
         5;
         ";
-        let mut l = Lexer {
-            ch: '5',
-            input: input.to_string(),
-        };
-        let lex = Lexer::new(&mut l, input.to_string());
+        let lex = Lexer::new(input);
         let mut prsr = Parser::new(lex);
 
         let program = prsr.parse_program();
@@ -293,11 +266,7 @@ mod test {
     #[test]
     fn test_if_expr() {
         let input = "if (x < y) { x }";
-        let mut l = Lexer {
-            ch: 'i',
-            input: input.to_string(),
-        };
-        let lex = Lexer::new(&mut l, input.to_string());
+        let lex = Lexer::new(input);
         let mut prsr = Parser::new(lex);
 
         let program = prsr.parse_program();
@@ -324,7 +293,7 @@ mod test {
                 }
 
                 if let Statment::Expr(expr) = &stmt.consequence.stmts[0] {
-                    if !test_ident(&expr, "x") {
+                    if !test_ident(expr, "x") {
                         panic!("Expected ident : {}, but got something else", "x");
                     }
                 }
@@ -340,11 +309,7 @@ mod test {
     #[test]
     fn test_if_expr_alternative() {
         let input = "if (x < y) { x } else { y }";
-        let mut l = Lexer {
-            ch: 'i',
-            input: input.to_string(),
-        };
-        let lex = Lexer::new(&mut l, input.to_string());
+        let lex = Lexer::new(input);
         let mut prsr = Parser::new(lex);
 
         let program = prsr.parse_program();
@@ -371,12 +336,16 @@ mod test {
                 }
 
                 if let Statment::Expr(expr) = &stmt.consequence.stmts[0] {
-                    if !test_ident(&expr, "x") {
+                    if !test_ident(expr, "x") {
                         return;
                     }
                 }
-                if let Statment::Expr(expr) = &stmt.alternative.clone().unwrap().stmts[0] {
-                    if !test_ident(&expr, "y") {
+                let alt = match &stmt.alternative {
+                    Some(ElseBranch::Block(block)) => block,
+                    _ => panic!("alternative is not an else block."),
+                };
+                if let Statment::Expr(expr) = &alt.stmts[0] {
+                    if !test_ident(expr, "y") {
                         panic!("Expected different identifier in else statment.");
                     }
                 }
@@ -386,14 +355,302 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_if_expr_else_if_chain() {
+        let input = "if (x < y) { x } else if (x > y) { y } else if (x == 1) { 1 } else { 0 }";
+        let lex = Lexer::new(input);
+        let mut prsr = Parser::new(lex);
+
+        let program = prsr.parse_program().unwrap();
+        check_parser_errors(prsr.errors);
+        assert_eq!(program.statments.len(), 1);
+        assert_eq!(
+            program.to_string(),
+            "if (x < y) xelse if (x > y) yelse if (x == 1) 1else 0"
+        );
+
+        let mut stmt = match &program.statments[0] {
+            Statment::Expr(Expression::If(stmt)) => stmt,
+            _ => panic!("Expression isn't an if expression."),
+        };
+        let mut depth = 0;
+        while let Some(ElseBranch::If(next)) = &stmt.alternative {
+            stmt = next;
+            depth += 1;
+        }
+        assert_eq!(depth, 2);
+        assert!(matches!(stmt.alternative, Some(ElseBranch::Block(_))));
+        assert_eq!(stmt.condition.to_string(), "(x == 1)");
+    }
+
+    #[test]
+    fn test_while_expr() {
+        let input = "while (x < 10) { if (x == 5) { break; } continue; }";
+        let lex = Lexer::new(input);
+        let mut prsr = Parser::new(lex);
+
+        let program = prsr.parse_program().unwrap();
+        check_parser_errors(prsr.errors);
+        assert_eq!(program.statments.len(), 1);
+
+        let stmt = match &program.statments[0] {
+            Statment::Expr(Expression::While(stmt)) => stmt,
+            _ => panic!("Expression isn't a while expression."),
+        };
+        assert_eq!(stmt.condition.to_string(), "(x < 10)");
+        assert_eq!(stmt.body.stmts.len(), 2);
+        assert!(matches!(stmt.body.stmts[1], Statment::Continue(_)));
+        assert_eq!(
+            program.to_string(),
+            "while (x < 10) if (x == 5) break;continue;"
+        );
+    }
+
+    #[test]
+    fn test_for_expr() {
+        let input = "for (c in range(0, n)) { if (c == 5) { continue; } c }";
+        let lex = Lexer::new(input);
+        let mut prsr = Parser::new(lex);
+
+        let program = prsr.parse_program().unwrap();
+        check_parser_errors(prsr.errors);
+        assert_eq!(program.statments.len(), 1);
+
+        let stmt = match &program.statments[0] {
+            Statment::Expr(Expression::For(stmt)) => stmt,
+            _ => panic!("Expression isn't a for expression."),
+        };
+        assert_eq!(stmt.ident.value, "c");
+        assert_eq!(stmt.iterable.to_string(), "range(0, n )");
+        assert_eq!(stmt.body.stmts.len(), 2);
+        assert_eq!(
+            program.to_string(),
+            "for (c in range(0, n )) if (c == 5) continue;c"
+        );
+    }
+
+    #[test]
+    fn test_loop_control_outside_loop() {
+        let tests = vec![
+            ("break;", vec!["break outside of a loop at 1:1"]),
+            (
+                "if (true) { continue; }",
+                vec!["continue outside of a loop at 1:13"],
+            ),
+            (
+                "while (true) { let f = fn() { break; }; }",
+                vec!["break outside of a loop at 1:31"],
+            ),
+            (
+                "while (true) { while (false) { continue; } break; }",
+                vec![],
+            ),
+            (
+                "while (true) { let f = fn() { while (true) { break; } }; }",
+                vec![],
+            ),
+        ];
+
+        for (input, expected) in tests {
+            let lex = Lexer::new(input);
+            let mut prsr = Parser::new(lex);
+            prsr.parse_program();
+            assert_eq!(error_messages(&prsr), expected, "input: {}", input);
+        }
+    }
+
+    #[test]
+    fn test_assign_expr() {
+        let tests = vec![
+            ("x = 5;", "x = 5"),
+            ("x += y * 2;", "x += (y * 2)"),
+            ("x %= 3", "x %= 3"),
+            ("a = b = c + 1", "a = b = (c + 1)"),
+            ("a = b == c || d", "a = ((b == c) || d)"),
+            ("if (x) { x -= 1 }", "if x x -= 1"),
+        ];
+        for (input, expected) in tests {
+            let lex = Lexer::new(input);
+            let mut prsr = Parser::new(lex);
+            let program = prsr.parse_program().unwrap();
+            check_parser_errors(prsr.errors);
+            assert_eq!(program.to_string(), expected);
+        }
+
+        let lex = Lexer::new("x /= 2");
+        let mut prsr = Parser::new(lex);
+        let program = prsr.parse_program().unwrap();
+        match &program.statments[0] {
+            Statment::Expr(Expression::Assign(assign)) => {
+                assert_eq!(assign.ident.value, "x");
+                assert_eq!(assign.operator, Some(Infix::Divide));
+                assert_eq!(assign.value.to_string(), "2");
+            }
+            _ => panic!("Expression isn't an assignment."),
+        }
+    }
+
+    #[test]
+    fn test_assign_invalid_target() {
+        let tests = vec![
+            (
+                "5 = 3;",
+                "invalid assignment target: 5, only identifiers can be assigned at 1:3",
+            ),
+            (
+                "a + b += 1",
+                "invalid assignment target: (a + b), only identifiers can be assigned at 1:7",
+            ),
+            (
+                "f() = 1",
+                "invalid assignment target: f( ), only identifiers can be assigned at 1:5",
+            ),
+        ];
+        for (input, expected) in tests {
+            let lex = Lexer::new(input);
+            let mut prsr = Parser::new(lex);
+            prsr.parse_program();
+            assert_eq!(error_messages(&prsr), vec![expected], "input: {}", input);
+        }
+    }
+
+    #[test]
+    fn test_array_literal() {
+        let lex = Lexer::new("[1, 2 * 2, 'three', []]");
+        let mut prsr = Parser::new(lex);
+        let program = prsr.parse_program().unwrap();
+        check_parser_errors(prsr.errors);
+
+        let arr = match &program.statments[0] {
+            Statment::Expr(Expression::Array(arr)) => arr,
+            _ => panic!("Expression isn't an array literal."),
+        };
+        assert_eq!(arr.elements.len(), 4);
+        test_int_lit(&arr.elements[0], 1);
+        assert_eq!(arr.elements[1].to_string(), "(2 * 2)");
+        assert_eq!(arr.elements[2].to_string(), "\"three\"");
+        assert!(matches!(&arr.elements[3], Expression::Array(a) if a.elements.is_empty()));
+    }
+
+    #[test]
+    fn test_trailing_commas() {
+        //every comma separated list may end with a comma, it parses like the list without one.
+        let tests = vec![
+            ("[1, 2,]", "[1, 2]"),
+            ("[1,]", "[1]"),
+            ("add(1, 2,)", "add(1, 2)"),
+            ("fn(x, y,) { x }", "fn(x, y) { x }"),
+            ("{'a': 1,}", "{'a': 1}"),
+        ];
+        for (input, without) in tests {
+            let mut prsr = Parser::new(Lexer::new(input));
+            let program = prsr.parse_program().unwrap();
+            check_parser_errors(prsr.errors);
+            let mut prsr = Parser::new(Lexer::new(without));
+            let expected = prsr.parse_program().unwrap();
+            assert_eq!(
+                program.to_string(),
+                expected.to_string(),
+                "input: {}",
+                input
+            );
+        }
+
+        //a comma on its own, or two in a row, is still an error.
+        let errors = vec![
+            ("[,]", "expected an expression, found ',' at 1:2"),
+            ("[1,,]", "expected an expression, found ',' at 1:4"),
+            ("add(,)", "expected an expression, found ',' at 1:5"),
+            ("fn(,) { 1 }", "expected identifier, found ',' at 1:4"),
+            ("{,}", "expected an expression, found ',' at 1:2"),
+            ("[1,", "expected an expression, found end of input at 1:4"),
+        ];
+        for (input, expected) in errors {
+            let mut prsr = Parser::new(Lexer::new(input));
+            prsr.parse_program();
+            assert_eq!(error_messages(&prsr)[0], expected, "input: {}", input);
+        }
+    }
+
+    #[test]
+    fn test_hash_literal() {
+        let tests = vec![
+            ("{}", "{}"),
+            ("{'one': 1, 'two': 2,}", "{\"one\": 1, \"two\": 2}"),
+            ("{1 + 1: a * 2, true: [1]}", "{(1 + 1): (a * 2), true: [1]}"),
+            ("{'a': {'b': 1}}['a']", "({\"a\": {\"b\": 1}}[\"a\"])"),
+            ("if (x) { {'k': x} }", "if x {\"k\": x}"),
+        ];
+        for (input, expected) in tests {
+            let lex = Lexer::new(input);
+            let mut prsr = Parser::new(lex);
+            let program = prsr.parse_program().unwrap();
+            check_parser_errors(prsr.errors);
+            assert_eq!(program.to_string(), expected);
+        }
+
+        let lex = Lexer::new("{'a': 1, 'b': 2}");
+        let mut prsr = Parser::new(lex);
+        let program = prsr.parse_program().unwrap();
+        let hash = match &program.statments[0] {
+            Statment::Expr(Expression::Hash(hash)) => hash,
+            _ => panic!("Expression isn't a hash literal."),
+        };
+        assert_eq!(hash.pairs.len(), 2);
+        test_int_lit(&hash.pairs[1].1, 2);
+
+        let errors = vec![
+            ("{'a' 1}", "expected ':', found '1' at 1:6"),
+            (
+                "{'a': 1 'b': 2}",
+                "expected ',' or '}', found string 'b' at 1:9",
+            ),
+            ("{'a': 1", "expected ',' or '}', found end of input at 1:8"),
+        ];
+        for (input, expected) in errors {
+            let lex = Lexer::new(input);
+            let mut prsr = Parser::new(lex);
+            prsr.parse_program();
+            assert_eq!(error_messages(&prsr)[0], expected, "input: {}", input);
+        }
+    }
+
+    #[test]
+    fn test_index_expr() {
+        let tests = vec![
+            ("arr[1 + 1]", "(arr[(1 + 1)])"),
+            (
+                "a * [1, 2, 3, 4][b * c] * d",
+                "((a * ([1, 2, 3, 4][(b * c)])) * d)",
+            ),
+            ("m[0][1]", "((m[0])[1])"),
+            ("-a[0]", "(-(a[0]))"),
+            (
+                "add(a * b[2], b[1], 2 * [1, 2][1])",
+                "add((a * (b[2])), (b[1]), (2 * ([1, 2][1])) )",
+            ),
+        ];
+        for (input, expected) in tests {
+            let lex = Lexer::new(input);
+            let mut prsr = Parser::new(lex);
+            let program = prsr.parse_program().unwrap();
+            check_parser_errors(prsr.errors);
+            assert_eq!(program.to_string(), expected);
+        }
+
+        let lex = Lexer::new("[1, 2");
+        let mut prsr = Parser::new(lex);
+        prsr.parse_program();
+        assert_eq!(
+            error_messages(&prsr),
+            vec!["expected ',' or ']', found end of input at 1:6"]
+        );
+    }
+
     #[test]
     fn test_call_expr_parse() {
         let input = "add(1, 2 * 3, 4 + 5);";
-        let mut l = Lexer {
-            ch: 'a',
-            input: input.to_string(),
-        };
-        let lex = Lexer::new(&mut l, input.to_string());
+        let lex = Lexer::new(input);
         let mut prsr = Parser::new(lex);
 
         let program = prsr.parse_program();
@@ -437,11 +694,7 @@ mod test {
     #[test]
     fn test_func_literal_parse() {
         let input = "fn(x, y) { x + y; }";
-        let mut l = Lexer {
-            ch: 'f',
-            input: input.to_string(),
-        };
-        let lex = Lexer::new(&mut l, input.to_string());
+        let lex = Lexer::new(input);
         let mut prsr = Parser::new(lex);
 
         let program = prsr.parse_program();
@@ -477,7 +730,7 @@ mod test {
                 }
 
                 if let Statment::Expr(ex) = &f.body.stmts[0] {
-                    test_infix_helper(&ex, "x", "+", "y");
+                    test_infix_helper(ex, "x", "+", "y");
                     return;
                 }
                 panic!("function body is not an Expression");
@@ -486,6 +739,30 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_func_literal_name_from_let() {
+        let tests = vec![
+            ("let add = fn(x, y) { x + y };", "add"),
+            ("let add = 1 + 2;", ""),
+            ("fn(x) { x };", ""),
+        ];
+        for (input, expected) in tests {
+            let lex = Lexer::new(input);
+            let mut prsr = Parser::new(lex);
+            let program = prsr.parse_program().unwrap();
+            check_parser_errors(prsr.errors);
+            let name = match &program.statments[0] {
+                Statment::Let(l) => match &l.value {
+                    Expression::Func(f) => f.name.clone(),
+                    _ => String::new(),
+                },
+                Statment::Expr(Expression::Func(f)) => f.name.clone(),
+                stmt => panic!("unexpected statment: {}", stmt),
+            };
+            assert_eq!(name, expected, "input: {}", input);
+        }
+    }
+
     #[test]
     fn test_func_param_parse() {
         struct Test<'a> {
@@ -508,11 +785,7 @@ mod test {
         ];
 
         for t_case in &tests {
-            let mut l = Lexer {
-                ch: '5',
-                input: t_case.inp.to_string(),
-            };
-            let lex = Lexer::new(&mut l, t_case.inp.to_string());
+            let lex = Lexer::new(t_case.inp);
             let mut prsr = Parser::new(lex);
 
             let program = prsr.parse_program();
@@ -533,7 +806,7 @@ mod test {
                     for (i, ident) in t_case.expected_params.iter().enumerate() {
                         test_lit_expr(&Expression::Identifier(f.params[i].clone()), *ident);
                     }
-                    return;
+                    continue;
                 }
                 panic!("Expression is not a function expression.");
             }
@@ -546,7 +819,7 @@ mod test {
         pub struct Pre<'a> {
             pub input: &'a str,
             pub op: &'a str,
-            pub int_value: i32,
+            pub int_value: i64,
         }
 
         let prefix_tests: Vec<Pre> = vec![
@@ -563,11 +836,7 @@ mod test {
         ];
 
         for t_case in prefix_tests.iter() {
-            let mut l = Lexer {
-                ch: '5',
-                input: t_case.input.to_string(),
-            };
-            let lex = Lexer::new(&mut l, t_case.input.to_string());
+            let lex = Lexer::new(t_case.input);
             let mut prsr = Parser::new(lex);
 
             let program = prsr.parse_program();
@@ -585,14 +854,14 @@ mod test {
             if let Statment::Expr(expr) = &program.unwrap().statments[0] {
                 match expr {
                     Expression::Prefix(p_ex) => {
-                        if p_ex.operator != t_case.op {
+                        if p_ex.operator.to_string() != t_case.op {
                             panic!(
                                 "unexpected operator. expected: {}, got: {}",
                                 t_case.op, p_ex.operator
                             );
                         }
                         test_int_lit(&p_ex.rhs, t_case.int_value);
-                        return;
+                        continue;
                     }
                     _ => panic!("Expression isn't an Prefix Expression "),
                 }
@@ -649,6 +918,18 @@ mod test {
                 op: "<",
                 rhs: Dtype::Int(5),
             },
+            Infix {
+                input: "5 <= 5;",
+                lhs: Dtype::Int(5),
+                op: "<=",
+                rhs: Dtype::Int(5),
+            },
+            Infix {
+                input: "5 >= 5;",
+                lhs: Dtype::Int(5),
+                op: ">=",
+                rhs: Dtype::Int(5),
+            },
             Infix {
                 input: "5 == 5;",
                 lhs: Dtype::Int(5),
@@ -675,17 +956,13 @@ mod test {
             },
             Infix {
                 input: "false == false",
-                lhs: Dtype::Bool(true),
+                lhs: Dtype::Bool(false),
                 op: "==",
                 rhs: Dtype::Bool(false),
             },
         ];
         for t_case in &infix_tests {
-            let mut l = Lexer {
-                ch: '5',
-                input: t_case.input.to_string(),
-            };
-            let lex = Lexer::new(&mut l, t_case.input.to_string());
+            let lex = Lexer::new(t_case.input);
             let mut prsr = Parser::new(lex);
             let program = prsr.parse_program();
             if program.is_none() {
@@ -702,7 +979,7 @@ mod test {
             if let Statment::Expr(expr) = &program.unwrap().statments[0] {
                 match expr {
                     Expression::Infix(infix) => {
-                        if infix.operator != t_case.op {
+                        if infix.operator.to_string() != t_case.op {
                             panic!(
                                 "unexpected operator. expected: {}, got: {}",
                                 t_case.op, infix.operator
@@ -721,9 +998,7 @@ mod test {
                             }
                         }
 
-                        // test_int_lit(&infix.lhs, t_case.lhs);
-                        // test_int_lit(&infix.rhs, t_case.rhs);
-                        return;
+                        continue;
                     }
                     _ => panic!("Expression isn't an infix Expression "),
                 }
@@ -733,7 +1008,7 @@ mod test {
         }
     }
 
-    fn test_int_lit(expr: &Expression, value: i32) -> bool {
+    fn test_int_lit(expr: &Expression, value: i64) -> bool {
         if let Expression::Int(num) = expr {
             if num.value != value {
                 panic!(
@@ -782,7 +1057,7 @@ mod test {
         T: Matchable + 'static,
     {
         if let Expression::Infix(inf) = expr {
-            if inf.operator != op {
+            if inf.operator.to_string() != op {
                 panic!(
                     "infix operator is not correct. Expected: {}, Got: {}",
                     op, inf.operator
@@ -847,6 +1122,38 @@ mod test {
                 inp: "5 < 4 != 3 > 4",
                 expected: "((5 < 4) != (3 > 4))",
             },
+            Tst {
+                inp: "a + b % c",
+                expected: "(a + (b % c))",
+            },
+            Tst {
+                inp: "2 ** 3 ** 2",
+                expected: "(2 ** (3 ** 2))",
+            },
+            Tst {
+                inp: "-2 ** 2 * 3",
+                expected: "((-(2 ** 2)) * 3)",
+            },
+            Tst {
+                inp: "a | b ^ c & d << 1 + 2",
+                expected: "(a | (b ^ (c & (d << (1 + 2)))))",
+            },
+            Tst {
+                inp: "~a & b == c >> 2",
+                expected: "(((~a) & b) == (c >> 2))",
+            },
+            Tst {
+                inp: "a || b && c == d",
+                expected: "(a || (b && (c == d)))",
+            },
+            Tst {
+                inp: "a && b || c && !d",
+                expected: "((a && b) || (c && (!d)))",
+            },
+            Tst {
+                inp: "a + 1 >= b * 2 == c <= d",
+                expected: "(((a + 1) >= (b * 2)) == (c <= d))",
+            },
             Tst {
                 inp: "3 + 4 * 5 == 3 * 1 + 4 * 5",
                 expected: "((3 + (4 * 5)) == ((3 * 1) + (4 * 5)))",
@@ -902,11 +1209,7 @@ mod test {
         ];
 
         for t_case in &tests {
-            let mut l = Lexer {
-                ch: t_case.inp.chars().next().unwrap(),
-                input: t_case.inp.to_string(),
-            };
-            let lex = Lexer::new(&mut l, t_case.inp.to_string());
+            let lex = Lexer::new(t_case.inp);
             let mut prsr = Parser::new(lex);
             let program = prsr.parse_program();
             if program.is_none() {
@@ -920,4 +1223,297 @@ mod test {
             }
         }
     }
+
+    #[test]
+    fn test_node_spans() {
+        let input = "let add = fn(x, y) {\n  x + y;\n};\nadd(1, 2 * 3);";
+        let lex = Lexer::new(input);
+        let mut prsr = Parser::new(lex);
+        let program = prsr.parse_program().unwrap();
+        check_parser_errors(prsr.errors);
+
+        let src = |span: Span| &input[span.start..span.end];
+
+        if let Statment::Let(let_stmt) = &program.statments[0] {
+            assert_eq!(src(let_stmt.span), "let add = fn(x, y) {\n  x + y;\n};");
+            assert_eq!((let_stmt.span.line, let_stmt.span.col), (1, 1));
+            assert_eq!(src(let_stmt.ident.span), "add");
+            if let Expression::Func(f) = &let_stmt.value {
+                assert_eq!(src(f.span), "fn(x, y) {\n  x + y;\n}");
+                assert_eq!(src(f.params[1].span), "y");
+                if let Statment::Expr(body) = &f.body.stmts[0] {
+                    assert_eq!(src(body.span()), "x + y");
+                    assert_eq!((body.span().line, body.span().col), (2, 3));
+                } else {
+                    panic!("function body is not an Expression");
+                }
+            } else {
+                panic!("Expression is not a function literal.");
+            }
+        } else {
+            panic!("Statment is not a Let statment");
+        }
+
+        if let Statment::Expr(Expression::Call(call)) = &program.statments[1] {
+            assert_eq!(src(call.span), "add(1, 2 * 3)");
+            assert_eq!((call.span.line, call.span.col), (4, 1));
+            assert_eq!(src(call.arguments[1].span()), "2 * 3");
+        } else {
+            panic!("Statment is not a call expression");
+        }
+    }
+
+    #[test]
+    fn test_parser_error_location() {
+        let lex = Lexer::new("let x = 5;\nlet y = );");
+        let mut prsr = Parser::new(lex);
+        prsr.parse_program();
+
+        assert_eq!(prsr.errors.len(), 1);
+        assert_eq!(prsr.errors[0].span.to_string(), "2:9");
+        assert_eq!(
+            prsr.errors[0].to_string(),
+            "expected an expression, found ')' at 2:9"
+        );
+    }
+
+    #[test]
+    fn test_string_literal_expr() {
+        let input = r#""hello world"; 'a' + "b""#;
+        let lex = Lexer::new(input);
+        let mut prsr = Parser::new(lex);
+        let program = prsr.parse_program().unwrap();
+        check_parser_errors(prsr.errors);
+
+        if let Statment::Expr(Expression::Str(s)) = &program.statments[0] {
+            assert_eq!(s.value, "hello world");
+        } else {
+            panic!("Expression is not a string literal");
+        }
+        assert_eq!(program.statments[1].to_string(), r#"("a" + "b")"#);
+    }
+
+    #[test]
+    fn test_lexer_errors_reported() {
+        let lex = Lexer::new("let s = 'unterminated;");
+        let mut prsr = Parser::new(lex);
+        prsr.parse_program();
+
+        assert_eq!(
+            error_messages(&prsr),
+            vec!["unterminated string literal at 1:9"]
+        );
+    }
+
+    #[test]
+    fn test_float_literal_expr() {
+        let lex = Lexer::new("1.5 * .5 + 1e3");
+        let mut prsr = Parser::new(lex);
+        let program = prsr.parse_program().unwrap();
+        check_parser_errors(prsr.errors);
+
+        assert_eq!(program.to_string(), "((1.5 * 0.5) + 1000.0)");
+    }
+
+    #[test]
+    fn test_parse_errors_on_every_failure_path() {
+        let tests = vec![
+            ("let = 5;", "expected identifier, found '=' at 1:5"),
+            ("let x 5;", "expected '=', found '5' at 1:7"),
+            ("let x = ;", "expected an expression, found ';' at 1:9"),
+            ("fn x) { x }", "expected '(', found 'x' at 1:4"),
+            ("fn(x y) { x }", "expected ',' or ')', found 'y' at 1:6"),
+            ("fn(1) { 1 }", "expected identifier, found '1' at 1:4"),
+            ("fn(x) x", "expected '{', found 'x' at 1:7"),
+            ("fn(x) { x", "expected '}', found end of input at 1:10"),
+            ("if x { 1 }", "expected '(', found 'x' at 1:4"),
+            ("if (x { 1 }", "expected ')', found '{' at 1:7"),
+            ("if (x) 1", "expected '{', found '1' at 1:8"),
+            (
+                "if (x) { 1 } else 2",
+                "expected 'if' or '{', found '2' at 1:19",
+            ),
+            ("while x { 1 }", "expected '(', found 'x' at 1:7"),
+            (
+                "for (1 in x) { 1 }",
+                "expected identifier, found '1' at 1:6",
+            ),
+            ("for (i x) { 1 }", "expected 'in', found 'x' at 1:8"),
+            ("(1 + 2", "expected ')', found end of input at 1:7"),
+            ("add(1 2)", "expected ',' or ')', found '2' at 1:7"),
+            ("a[1", "expected ']', found end of input at 1:4"),
+            ("1 +", "expected an expression, found end of input at 1:4"),
+        ];
+        for (input, expected) in tests {
+            let lex = Lexer::new(input);
+            let mut prsr = Parser::new(lex);
+            prsr.parse_program();
+            assert_eq!(error_messages(&prsr), vec![expected], "input: {}", input);
+        }
+    }
+
+    #[test]
+    fn test_parse_error_fields() {
+        let lex = Lexer::new("let x = [1, 2;");
+        let mut prsr = Parser::new(lex);
+        prsr.parse_program();
+
+        assert_eq!(prsr.errors.len(), 1);
+        let err = &prsr.errors[0];
+        assert_eq!(err.expected, vec![TokenType::Comma, TokenType::Rsquare]);
+        assert_eq!(err.found.tok_type, TokenType::Semicolon);
+        assert_eq!((err.span.line, err.span.col), (1, 14));
+    }
+
+    #[test]
+    fn test_parse_error_recovery() {
+        //every bad statment is reported, and the good ones around them still parse.
+        let input = "let = 1;
+let a = 2;
+let b = (3;
+if (a) { let = 4; a + 1 } else { b }
+let c = {1: 2 3};
+fn(x) { x( };
+a + b";
+        let lex = Lexer::new(input);
+        let mut prsr = Parser::new(lex);
+        let program = prsr.parse_program().unwrap();
+
+        assert_eq!(
+            error_messages(&prsr),
+            vec![
+                "expected identifier, found '=' at 1:5",
+                "expected ')', found ';' at 3:11",
+                "expected identifier, found '=' at 4:14",
+                "expected ',' or '}', found '3' at 5:15",
+                "expected an expression, found '}' at 6:12",
+            ]
+        );
+        let stmts: Vec<String> = program.statments.iter().map(|s| s.to_string()).collect();
+        assert_eq!(
+            stmts,
+            vec!["let a = 2;", "if a (a + 1)else b", "fn (x) ", "(a + b)"]
+        );
+    }
+
+    #[test]
+    fn test_nesting_limit() {
+        let tests = vec![
+            (
+                "(".repeat(10_000),
+                "expression nested too deeply, the limit is 200 at 1:201",
+            ),
+            //a block is a level of its own, so each of these ifs counts twice.
+            (
+                "if (x) { ".repeat(10_000),
+                "expression nested too deeply, the limit is 200 at 1:901",
+            ),
+            //calls, indexes and operators nest their lhs, each step of a chain is a level.
+            (
+                format!("a{}", "[0]".repeat(1500)),
+                "expression nested too deeply, the limit is 200 at 1:597",
+            ),
+            (
+                format!("1{}", " + 1".repeat(3000)),
+                "expression nested too deeply, the limit is 200 at 1:799",
+            ),
+            (
+                format!("f{}", "()".repeat(3000)),
+                "expression nested too deeply, the limit is 200 at 1:400",
+            ),
+            (
+                format!("1{}", " ** 1".repeat(3000)),
+                "expression nested too deeply, the limit is 200 at 1:998",
+            ),
+        ];
+        for (input, expected) in tests {
+            let lex = Lexer::new(&input);
+            let mut prsr = Parser::new(lex);
+            prsr.parse_program();
+            assert_eq!(error_messages(&prsr)[0], expected);
+        }
+
+        let input = format!("{}1{}", "(".repeat(199), ")".repeat(199));
+        let lex = Lexer::new(&input);
+        let mut prsr = Parser::new(lex);
+        let program = prsr.parse_program().unwrap();
+        check_parser_errors(prsr.errors);
+        assert_eq!(program.to_string(), "1");
+
+        //chains just under the limit parse, ** is right associative but still a level per operand.
+        let inputs = vec![
+            format!("1{}", " ** 1".repeat(120)),
+            format!("1{}", " + 1".repeat(190)),
+            format!("a{}", "[0]".repeat(190)),
+            format!("f{}", "()".repeat(190)),
+        ];
+        for input in inputs {
+            let lex = Lexer::new(&input);
+            let mut prsr = Parser::new(lex);
+            prsr.parse_program().unwrap();
+            check_parser_errors(prsr.errors);
+        }
+    }
+
+    #[test]
+    fn test_long_else_if_chain() {
+        //the branches of a chain aren't nested in the source and can go past the nesting limit.
+        let input = format!("{}{{ 0 }}", "if (x) { 1 } else ".repeat(10_000));
+        let lex = Lexer::new(&input);
+        let mut prsr = Parser::new(lex);
+        let program = prsr.parse_program().unwrap();
+        check_parser_errors(prsr.errors);
+
+        let if_stmt = match &program.statments[0] {
+            Statment::Expr(Expression::If(if_stmt)) => if_stmt,
+            _ => panic!("Statment is not an if expression"),
+        };
+        let mut branches = 1;
+        let mut curr = if_stmt.as_ref();
+        while let Some(ElseBranch::If(next)) = &curr.alternative {
+            //each if spans to the end of the chain.
+            assert_eq!(next.span.end, input.len());
+            curr = next;
+            branches += 1;
+        }
+        assert_eq!(branches, 10_000);
+        assert!(matches!(curr.alternative, Some(ElseBranch::Block(_))));
+        assert!(program.statments[0] == program.statments[0].clone());
+    }
+
+    #[test]
+    fn test_corpus_prefixes() {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/fuzz/corpus/parse");
+        let mut paths: Vec<_> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        paths.sort();
+        assert!(!paths.is_empty());
+
+        for path in paths {
+            //the fuzzer may add inputs that aren't valid UTF-8, the parser only takes strings.
+            let source = match String::from_utf8(std::fs::read(&path).unwrap()) {
+                Ok(source) => source,
+                Err(_) => continue,
+            };
+            //the seed files are valid programs.
+            if path.extension().is_some_and(|ext| ext == "qk") {
+                let mut prsr = Parser::new(Lexer::new(&source));
+                prsr.parse_program();
+                check_parser_errors(prsr.errors);
+            }
+            //every prefix is what an editor sees while the file is being typed, each has to
+            //parse, with or without errors, without panicking or hanging.
+            for (end, _) in source.char_indices() {
+                let mut prsr = Parser::new(Lexer::new(&source[..end]));
+                let program = prsr.parse_program().unwrap();
+                let _ = program.to_string();
+            }
+        }
+    }
+
+    fn error_messages(prsr: &Parser) -> Vec<String> {
+        prsr.errors.iter().map(|err| err.to_string()).collect()
+    }
 }
@@ -5,12 +5,16 @@ use std::{
 
 use lazy_static::lazy_static;
 
-#[derive(Debug, PartialEq, Clone, Copy, Eq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum TokenType {
     Ident,
     Illegal,
     EOF,
-    Int(i32),
+    Int(i64),
+    //integer literal too large for an i64, the literal holds its decimal digits.
+    BigInt,
+    Float(f64),
+    Str,
     Plus,
     Minus,
     EQ,
@@ -18,14 +22,34 @@ pub enum TokenType {
     Not,
     Larrow,
     Rarrow,
+    LarrowEQ,
+    RarrowEQ,
+    And,
+    Or,
+    Percent,
+    Power,
+    Ampersand,
+    Pipe,
+    Caret,
+    Tilde,
+    LShift,
+    RShift,
+    PlusAssign,
+    MinusAssign,
+    AsteriskAssign,
+    FslashAssign,
+    PercentAssign,
     Fslash,
     Assign,
     Comma,
+    Colon,
     Semicolon,
     Lparen,
     Rparen,
     Lbrack,
     Rbrack,
+    Lsquare,
+    Rsquare,
     Asterisk,
     Let,
     Function,
@@ -34,12 +58,45 @@ pub enum TokenType {
     True,
     False,
     Return,
+    While,
+    Break,
+    Continue,
+    For,
+    In,
+}
+
+//location of a piece of source, byte range is [start, end), line and col are 1-based.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub col: usize,
+}
+
+impl Span {
+    //span covering both self and other, assuming other doesn't start before self.
+    pub fn to(&self, other: &Span) -> Span {
+        Span {
+            start: self.start,
+            end: other.end.max(self.end),
+            line: self.line,
+            col: self.col,
+        }
+    }
+}
+
+impl Display for Span {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.line, self.col)
+    }
 }
 
 #[derive(Clone, PartialEq)]
 pub struct Token {
     pub tok_type: TokenType,
     pub literal: String,
+    pub span: Span,
 }
 
 impl Display for Token {
@@ -47,6 +104,9 @@ impl Display for Token {
         match self.tok_type {
             TokenType::Ident => write!(f, "Ident[{}]", self.literal),
             TokenType::Int(s) => write!(f, "Int[{}]", s),
+            TokenType::Float(s) => write!(f, "Float[{:?}]", s),
+            TokenType::BigInt => write!(f, "BigInt[{}]", self.literal),
+            TokenType::Str => write!(f, "Str[{:?}]", self.literal),
             _ => write!(f, "{:?}", self.tok_type),
         }
     }
@@ -67,18 +127,46 @@ impl Display for TokenType {
             Self::Ident => write!(f, "Ident"),
             Self::Minus => write!(f, "Minus"),
             Self::Comma => write!(f, "Comma"),
+            Self::Colon => write!(f, ":"),
             Self::False => write!(f, "False"),
             Self::Larrow => write!(f, "<"),
             Self::Rarrow => write!(f, ">"),
+            Self::LarrowEQ => write!(f, "<="),
+            Self::RarrowEQ => write!(f, ">="),
+            Self::And => write!(f, "&&"),
+            Self::Or => write!(f, "||"),
+            Self::Percent => write!(f, "%"),
+            Self::Power => write!(f, "**"),
+            Self::Ampersand => write!(f, "&"),
+            Self::Pipe => write!(f, "|"),
+            Self::Caret => write!(f, "^"),
+            Self::Tilde => write!(f, "~"),
+            Self::LShift => write!(f, "<<"),
+            Self::RShift => write!(f, ">>"),
+            Self::PlusAssign => write!(f, "+="),
+            Self::MinusAssign => write!(f, "-="),
+            Self::AsteriskAssign => write!(f, "*="),
+            Self::FslashAssign => write!(f, "/="),
+            Self::PercentAssign => write!(f, "%="),
             Self::Fslash => write!(f, "/"),
             Self::Assign => write!(f, "Assign"),
             Self::Lparen => write!(f, "("),
             Self::Rparen => write!(f, ")"),
             Self::Lbrack => write!(f, "Left Bracket"),
             Self::Rbrack => write!(f, "Right Bracket"),
+            Self::Lsquare => write!(f, "["),
+            Self::Rsquare => write!(f, "]"),
             Self::Return => write!(f, "Return"),
+            Self::While => write!(f, "While"),
+            Self::Break => write!(f, "Break"),
+            Self::Continue => write!(f, "Continue"),
+            Self::For => write!(f, "For"),
+            Self::In => write!(f, "In"),
             Self::Illegal => write!(f, "Illegal"),
             Self::Int(num) => write!(f, "{}", num),
+            Self::Float(num) => write!(f, "{:?}", num),
+            Self::BigInt => write!(f, "BigInt"),
+            Self::Str => write!(f, "String"),
             Self::Asterisk => write!(f, "Astrisk"),
             Self::Semicolon => write!(f, "Semicolon"),
             Self::Not => write!(f, "Not"),
@@ -96,9 +184,14 @@ lazy_static! {
         ("else", TokenType::Else),
         ("true", TokenType::True),
         ("false", TokenType::False),
-        ("return", TokenType::Return)
+        ("return", TokenType::Return),
+        ("while", TokenType::While),
+        ("break", TokenType::Break),
+        ("continue", TokenType::Continue),
+        ("for", TokenType::For),
+        ("in", TokenType::In)
     ]);
 }
 pub fn lookup_ident(ident: &str) -> TokenType {
-    keywords.get(ident).unwrap_or(&TokenType::Ident).clone()
+    *keywords.get(ident).unwrap_or(&TokenType::Ident)
 }
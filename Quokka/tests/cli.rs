@@ -0,0 +1,144 @@
+#![allow(clippy::needless_return)]
+
+//runs the quokka binary on scripts written to the temp directory.
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static SCRIPT_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+//a script file unique to this test run, removed when dropped.
+struct Script(PathBuf);
+
+impl Script {
+    fn new(source: &str) -> Script {
+        let name = format!(
+            "quokka-cli-{}-{}.qk",
+            std::process::id(),
+            SCRIPT_COUNT.fetch_add(1, Ordering::SeqCst)
+        );
+        let path = std::env::temp_dir().join(name);
+        fs::write(&path, source).unwrap();
+        return Script(path);
+    }
+
+    fn path(&self) -> &str {
+        self.0.to_str().unwrap()
+    }
+
+    fn run(&self, args: &[&str]) -> Output {
+        return run(self.path(), args);
+    }
+}
+
+impl Drop for Script {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+fn run(path: &str, args: &[&str]) -> Output {
+    return Command::new(env!("CARGO_BIN_EXE_quokka"))
+        .arg(path)
+        .args(args)
+        .output()
+        .unwrap();
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8(output.stderr.clone()).unwrap()
+}
+
+#[test]
+fn test_script_success() {
+    let script = Script::new("let add = fn(x, y) { x + y };\nputs(add(2, 3));\n");
+    let output = script.run(&[]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "5\n");
+    assert_eq!(stderr(&output), "");
+}
+
+#[test]
+fn test_script_args() {
+    let script = Script::new("puts(len(args));\nfor (arg in args) { puts(arg) }\n");
+    let output = script.run(&["foo", "bar baz"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "2\nfoo\nbar baz\n");
+
+    let output = script.run(&[]);
+    assert_eq!(stdout(&output), "0\n");
+}
+
+#[test]
+fn test_script_top_level_return() {
+    let script = Script::new("puts(1);\nif (true) { return 5; }\nputs(2);\n");
+    let output = script.run(&[]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "1\n");
+}
+
+#[test]
+fn test_script_parse_errors() {
+    //every error is reported as file:line:col, and nothing runs.
+    let script = Script::new("puts(1);\nlet f = fn(a b) { a };\nlet = 2;\n");
+    let output = script.run(&[]);
+    assert_eq!(output.status.code(), Some(65));
+    assert_eq!(stdout(&output), "");
+    let path = script.path();
+    assert_eq!(
+        stderr(&output),
+        format!(
+            "{path}:2:14: expected ',' or ')', found 'b'\n{path}:3:5: expected identifier, found '='\n"
+        )
+    );
+}
+
+#[test]
+fn test_script_runtime_error() {
+    let script = Script::new("let f = fn(x) { x + true };\nputs(1);\nf(1);\nputs(2);\n");
+    let output = script.run(&[]);
+    assert_eq!(output.status.code(), Some(70));
+    assert_eq!(stdout(&output), "1\n");
+    let path = script.path();
+    assert_eq!(
+        stderr(&output),
+        format!(
+            "Traceback (most recent call last):\n  f called at {path}:3:1\nError: type mismatch: INTEGER + BOOLEAN at {path}:1:17\n"
+        )
+    );
+}
+
+#[test]
+fn test_script_missing_file() {
+    let path = std::env::temp_dir().join("quokka-cli-missing.qk");
+    let output = run(path.to_str().unwrap(), &[]);
+    assert_eq!(output.status.code(), Some(66));
+    assert!(stderr(&output).starts_with(&format!("quokka: can't read {}", path.display())));
+}
+
+#[test]
+fn test_script_shebang_spans() {
+    //the shebang is a comment, locations on later lines are unchanged by it.
+    let script = Script::new("#!/usr/bin/env quokka\nputs(args[0]);\nputs(1 / 0);\n");
+    let output = script.run(&["hi"]);
+    assert_eq!(output.status.code(), Some(70));
+    assert_eq!(stdout(&output), "hi\n");
+    assert_eq!(
+        stderr(&output),
+        format!("Error: division by zero at {}:3:6\n", script.path())
+    );
+
+    //a byte order mark before the shebang is skipped too.
+    let script = Script::new("\u{feff}#!/usr/bin/env quokka\nlet = 1;\n");
+    let output = script.run(&[]);
+    assert_eq!(output.status.code(), Some(65));
+    assert_eq!(
+        stderr(&output),
+        format!("{}:2:5: expected identifier, found '='\n", script.path())
+    );
+}